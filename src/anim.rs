@@ -0,0 +1,123 @@
+use std::f32::consts::PI;
+
+/// One full turn. `wave(TAU)` matches `sin(t)`, and `wave(TAU / n)` repeats n times as often.
+pub const TAU: f32 = 2.0 * PI;
+
+/// Easing curves mapping linear progress in `0..=1` to eased progress.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Ease {
+    Linear,
+    InQuad,
+    OutQuad,
+    OutBack,
+}
+
+impl Ease {
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.max(0.0).min(1.0);
+        match self {
+            Ease::Linear => t,
+            Ease::InQuad => t * t,
+            Ease::OutQuad => t * (2.0 - t),
+            Ease::OutBack => {
+                let s = 1.70158;
+                let t = t - 1.0;
+                t * t * ((s + 1.0) * t + s) + 1.0
+            },
+        }
+    }
+}
+
+/// Moves a value from `from` to `to` over `duration` seconds.
+#[derive(Debug, Copy, Clone)]
+pub struct Tween {
+    pub from: f32,
+    pub to: f32,
+    pub duration: f32,
+    pub ease: Ease,
+}
+
+impl Tween {
+    pub fn at(&self, t: f32) -> f32 {
+        let progress = if self.duration > 0.0 { t / self.duration } else { 1.0 };
+        self.from + (self.to - self.from) * self.ease.apply(progress)
+    }
+}
+
+/// Tweens played back to back, each starting where the last one ended.
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    start: f32,
+    tweens: Vec<Tween>,
+}
+
+impl Timeline {
+    pub fn new(start: f32) -> Self {
+        Timeline {
+            start: start,
+            tweens: Vec::new(),
+        }
+    }
+    fn end(&self) -> f32 {
+        self.tweens.last().map(|t| t.to).unwrap_or(self.start)
+    }
+    pub fn to(mut self, value: f32, duration: f32, ease: Ease) -> Self {
+        let from = self.end();
+        self.tweens.push(Tween {
+            from: from,
+            to: value,
+            duration: duration,
+            ease: ease,
+        });
+        self
+    }
+    pub fn hold(self, duration: f32) -> Self {
+        let value = self.end();
+        self.to(value, duration, Ease::Linear)
+    }
+    pub fn length(&self) -> f32 {
+        self.tweens.iter().map(|t| t.duration).sum()
+    }
+    pub fn at(&self, mut t: f32) -> f32 {
+        for tween in self.tweens.iter() {
+            if t < tween.duration {
+                return tween.at(t);
+            }
+            t -= tween.duration;
+        }
+        self.end()
+    }
+}
+
+/// Named one-shot animations. Triggering one restarts its timeline; anything drawing the game
+/// reads the current value through `Context::value`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// The enemy took a hit: recoil and damage flicker.
+    HitFlash,
+    /// The player lunges at the enemy.
+    Attack,
+    /// The player took a hit.
+    Hurt,
+    /// Strength of the screen shake.
+    ScreenShake,
+    /// The player won the battle.
+    Victory,
+}
+
+impl Event {
+    pub fn timeline(&self) -> Timeline {
+        match self {
+            Event::HitFlash => Timeline::new(1.0).to(0.0, 0.3, Ease::Linear),
+            Event::Attack => Timeline::new(0.0).to(1.0, 0.12, Ease::OutQuad).to(0.0, 0.18, Ease::InQuad),
+            Event::Hurt => Timeline::new(1.0).to(0.0, 0.4, Ease::OutQuad),
+            Event::ScreenShake => Timeline::new(1.0).to(0.0, 0.4, Ease::OutQuad),
+            Event::Victory => Timeline::new(0.0).to(1.0, 0.5, Ease::OutBack),
+        }
+    }
+}
+
+/// A repeating wave in `-1..=1` with a period of `period` seconds.
+pub fn wave(t: f32, period: f32) -> f32 {
+    (t / period * 2.0 * PI).sin()
+}
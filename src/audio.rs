@@ -0,0 +1,357 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use stdweb::{js, Value};
+
+/// One-shot sound effects.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Sound {
+    Correct,
+    Wrong,
+    Hit,
+    Step,
+    Firework,
+    LevelUp,
+}
+
+impl Sound {
+    /// The oscillator shape the sound is played with.
+    fn wave(&self) -> &'static str {
+        match self {
+            Sound::Correct | Sound::LevelUp => "triangle",
+            Sound::Wrong => "sawtooth",
+            Sound::Hit | Sound::Firework => "square",
+            Sound::Step => "sine",
+        }
+    }
+    /// The notes that make up the sound as (frequency, start, length), in hertz and seconds.
+    fn notes(&self) -> &'static [(f32, f32, f32)] {
+        match self {
+            Sound::Correct => &[(523.3, 0.0, 0.12), (784.0, 0.08, 0.2)],
+            Sound::Wrong => &[(196.0, 0.0, 0.15), (146.8, 0.12, 0.3)],
+            Sound::Hit => &[(110.0, 0.0, 0.1)],
+            Sound::Step => &[(80.0, 0.0, 0.05)],
+            Sound::Firework => &[(880.0, 0.0, 0.05), (1318.5, 0.05, 0.05), (1760.0, 0.1, 0.15)],
+            Sound::LevelUp => &[(523.3, 0.0, 0.15), (659.3, 0.15, 0.15), (784.0, 0.3, 0.15), (1046.5, 0.45, 0.4)],
+        }
+    }
+    fn gain(&self) -> f32 {
+        match self {
+            Sound::Step => 0.1,
+            Sound::Hit | Sound::Wrong => 0.25,
+            _ => 0.3,
+        }
+    }
+}
+
+/// Background music, looped for as long as its state lasts.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Track {
+    Title,
+    Overland,
+    Battle,
+    Boss,
+    Shop,
+}
+
+impl Track {
+    /// The track for a state, by `State::name`.
+    pub fn for_state(state: &str, boss: bool) -> Option<Track> {
+        match state {
+            "title" => Some(Track::Title),
+            "overland" => Some(Track::Overland),
+            "battle" if boss => Some(Track::Boss),
+            "battle" => Some(Track::Battle),
+            "shop" | "creator" => Some(Track::Shop),
+            _ => None,
+        }
+    }
+    /// Note frequencies played one per beat, with zero for a rest, and the beat length in seconds.
+    fn pattern(&self) -> (&'static [f32], f32) {
+        match self {
+            Track::Title => (&[261.6, 329.6, 392.0, 523.3, 392.0, 329.6, 0.0, 0.0], 0.35),
+            Track::Overland => (&[392.0, 0.0, 440.0, 392.0, 329.6, 0.0, 293.7, 329.6, 392.0, 0.0, 523.3, 0.0, 440.0, 392.0, 0.0, 0.0], 0.25),
+            Track::Battle => (&[220.0, 220.0, 261.6, 220.0, 329.6, 220.0, 293.7, 261.6], 0.15),
+            Track::Boss => (&[110.0, 116.5, 110.0, 164.8, 110.0, 155.6, 146.8, 130.8], 0.13),
+            Track::Shop => (&[349.2, 440.0, 523.3, 440.0, 392.0, 493.9, 587.3, 493.9], 0.3),
+        }
+    }
+}
+
+/// Where sound actually goes. The web build plays through WebAudio; native builds, which have no
+/// audio, use `NullAudio`.
+pub trait AudioBackend {
+    /// Browsers keep audio suspended until the page has been interacted with.
+    fn resume(&mut self);
+    fn tone(&mut self, wave: &str, freq: f32, at: f32, length: f32, gain: f32);
+    fn start_loop(&mut self, notes: &[f32], beat: f32, gain: f32);
+    fn stop_loop(&mut self);
+    fn set_loop_gain(&mut self, gain: f32);
+}
+
+/// A call made on a backend, as recorded by `NullAudio`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AudioCall {
+    Resume,
+    Tone { freq: f32, gain: f32 },
+    StartLoop { notes: Vec<f32>, gain: f32 },
+    StopLoop,
+    LoopGain(f32),
+}
+
+/// Plays nothing, but keeps a log of what it was asked to play. Clone it before handing it to
+/// `Audio` to keep a handle on the log.
+#[derive(Debug, Clone, Default)]
+pub struct NullAudio {
+    pub calls: Rc<RefCell<Vec<AudioCall>>>,
+}
+
+impl NullAudio {
+    fn record(&mut self, call: AudioCall) {
+        self.calls.borrow_mut().push(call);
+    }
+}
+
+impl AudioBackend for NullAudio {
+    fn resume(&mut self) {
+        self.record(AudioCall::Resume);
+    }
+    fn tone(&mut self, _wave: &str, freq: f32, _at: f32, _length: f32, gain: f32) {
+        self.record(AudioCall::Tone { freq: freq, gain: gain });
+    }
+    fn start_loop(&mut self, notes: &[f32], _beat: f32, gain: f32) {
+        self.record(AudioCall::StartLoop { notes: notes.to_vec(), gain: gain });
+    }
+    fn stop_loop(&mut self) {
+        self.record(AudioCall::StopLoop);
+    }
+    fn set_loop_gain(&mut self, gain: f32) {
+        self.record(AudioCall::LoopGain(gain));
+    }
+}
+
+/// Synthesizes everything with oscillators, so there are no sound files to load.
+pub struct WebAudio {
+    ctx: Value,
+    music: Value,
+}
+
+impl WebAudio {
+    pub fn new() -> Self {
+        let ctx = js! {
+            var Context = window.AudioContext || window.webkitAudioContext;
+            return Context ? new Context() : null;
+        };
+        let music = js! {
+            return { gain: null, timer: null };
+        };
+        WebAudio {
+            ctx: ctx,
+            music: music,
+        }
+    }
+}
+
+impl AudioBackend for WebAudio {
+    fn resume(&mut self) {
+        js! { @(no_return)
+            var ctx = @{&self.ctx};
+            if (ctx && ctx.state === "suspended") {
+                ctx.resume();
+            }
+        }
+    }
+    fn tone(&mut self, wave: &str, freq: f32, at: f32, length: f32, gain: f32) {
+        js! { @(no_return)
+            var ctx = @{&self.ctx};
+            if (!ctx) {
+                return;
+            }
+            var gain = @{gain};
+            var start = ctx.currentTime + @{at};
+            var end = start + @{length};
+            var osc = ctx.createOscillator();
+            var amp = ctx.createGain();
+            osc.type = @{wave};
+            osc.frequency.value = @{freq};
+            amp.gain.setValueAtTime(gain, start);
+            amp.gain.exponentialRampToValueAtTime(0.0001, end);
+            osc.connect(amp);
+            amp.connect(ctx.destination);
+            osc.start(start);
+            osc.stop(end);
+        }
+    }
+    fn start_loop(&mut self, notes: &[f32], beat: f32, gain: f32) {
+        self.stop_loop();
+        let notes = notes.to_vec();
+        js! { @(no_return)
+            var ctx = @{&self.ctx};
+            var music = @{&self.music};
+            if (!ctx) {
+                return;
+            }
+            var notes = @{notes};
+            var beat = @{beat};
+            var i = 0;
+            music.gain = ctx.createGain();
+            music.gain.gain.value = @{gain};
+            music.gain.connect(ctx.destination);
+            var out = music.gain;
+            music.timer = setInterval(function() {
+                var freq = notes[i % notes.length];
+                i += 1;
+                if (freq <= 0 || ctx.state !== "running") {
+                    return;
+                }
+                var t = ctx.currentTime;
+                var osc = ctx.createOscillator();
+                var amp = ctx.createGain();
+                osc.type = "triangle";
+                osc.frequency.value = freq;
+                amp.gain.setValueAtTime(0.3, t);
+                amp.gain.exponentialRampToValueAtTime(0.0001, t + beat * 0.9);
+                osc.connect(amp);
+                amp.connect(out);
+                osc.start(t);
+                osc.stop(t + beat);
+            }, beat * 1000);
+        }
+    }
+    fn stop_loop(&mut self) {
+        js! { @(no_return)
+            var music = @{&self.music};
+            if (music.timer) {
+                clearInterval(music.timer);
+                music.timer = null;
+            }
+            if (music.gain) {
+                music.gain.disconnect();
+                music.gain = null;
+            }
+        }
+    }
+    fn set_loop_gain(&mut self, gain: f32) {
+        js! { @(no_return)
+            var music = @{&self.music};
+            if (music.gain) {
+                music.gain.gain.value = @{gain};
+            }
+        }
+    }
+}
+
+/// Plays sounds and music through a backend, mixing the effects and music channels under the
+/// master volume.
+pub struct Audio {
+    backend: Box<dyn AudioBackend>,
+    track: Option<Track>,
+    master: f32,
+    effects: f32,
+    music: f32,
+}
+
+impl Audio {
+    pub fn new() -> Self {
+        if cfg!(any(target_arch = "wasm32", target_arch = "asmjs")) {
+            Audio::with_backend(Box::new(WebAudio::new()))
+        } else {
+            Audio::with_backend(Box::new(NullAudio::default()))
+        }
+    }
+    pub fn with_backend(backend: Box<dyn AudioBackend>) -> Self {
+        Audio {
+            backend: backend,
+            track: None,
+            master: 1.0,
+            effects: 1.0,
+            music: 1.0,
+        }
+    }
+    pub fn resume(&mut self) {
+        self.backend.resume();
+    }
+    /// Sets each channel from 0 to 1.
+    pub fn set_volumes(&mut self, master: f32, effects: f32, music: f32) {
+        self.master = master;
+        self.effects = effects;
+        self.music = music;
+        self.backend.set_loop_gain(self.master * self.music);
+    }
+    pub fn play(&mut self, sound: Sound) {
+        let gain = sound.gain() * self.master * self.effects;
+        if gain <= 0.0 {
+            return;
+        }
+        for &(freq, at, length) in sound.notes() {
+            self.backend.tone(sound.wave(), freq, at, length, gain);
+        }
+    }
+    /// Switches to `track`, leaving the music alone if it is already playing.
+    pub fn set_music(&mut self, track: Option<Track>) {
+        if track == self.track {
+            return;
+        }
+        self.track = track;
+        match track {
+            Some(track) => {
+                let (notes, beat) = track.pattern();
+                self.backend.start_loop(notes, beat, self.master * self.music);
+            },
+            None => self.backend.stop_loop(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorded() -> (Audio, Rc<RefCell<Vec<AudioCall>>>) {
+        let backend = NullAudio::default();
+        let calls = backend.calls.clone();
+        (Audio::with_backend(Box::new(backend)), calls)
+    }
+
+    #[test]
+    fn same_track_keeps_playing() {
+        let (mut audio, calls) = recorded();
+        audio.set_music(Some(Track::Battle));
+        audio.set_music(Some(Track::Battle));
+        let starts = calls.borrow().iter().filter(|c| match c { AudioCall::StartLoop { .. } => true, _ => false }).count();
+        assert_eq!(starts, 1);
+    }
+
+    #[test]
+    fn no_track_stops_music() {
+        let (mut audio, calls) = recorded();
+        audio.set_music(Some(Track::Title));
+        audio.set_music(None);
+        assert_eq!(calls.borrow().last(), Some(&AudioCall::StopLoop));
+    }
+
+    #[test]
+    fn silent_effects_play_nothing() {
+        let (mut audio, calls) = recorded();
+        audio.set_volumes(1.0, 0.0, 1.0);
+        calls.borrow_mut().clear();
+        audio.play(Sound::Correct);
+        assert!(calls.borrow().is_empty());
+        audio.set_volumes(1.0, 0.5, 1.0);
+        audio.play(Sound::Correct);
+        assert!(calls.borrow().iter().any(|c| match c { AudioCall::Tone { .. } => true, _ => false }));
+    }
+
+    #[test]
+    fn music_gain_is_master_times_music() {
+        let (mut audio, calls) = recorded();
+        audio.set_volumes(0.5, 1.0, 0.4);
+        assert_eq!(calls.borrow().last(), Some(&AudioCall::LoopGain(0.5 * 0.4)));
+        audio.set_music(Some(Track::Overland));
+        let last = calls.borrow().last().cloned();
+        match last {
+            Some(AudioCall::StartLoop { gain, .. }) => assert_eq!(gain, 0.5 * 0.4),
+            call => panic!("expected the loop to start, got {:?}", call),
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use yew::{html, Html};
+
+use crate::context::*;
+use crate::anim::TAU;
+use crate::player::*;
+use crate::{Model, Overland};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Facing {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Facing {
+    /// The way a movement vector from `Direction::direction` faces, or `None` when standing still.
+    /// Sideways wins on diagonals so the profile shows while walking.
+    pub fn from_direction(d: [f32; 2]) -> Option<Facing> {
+        let sideways = d[0] != 0.0 && d[0].abs() * 2.0 >= d[1].abs();
+        if sideways && d[0] > 0.0 {
+            Some(Facing::Right)
+        } else if sideways {
+            Some(Facing::Left)
+        } else if d[1] > 0.0 {
+            Some(Facing::Up)
+        } else if d[1] < 0.0 {
+            Some(Facing::Down)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Appearance {
+    pub color: &'static str,
+    pub hat: Option<Hat>,
+}
+
+impl Appearance {
+    pub fn new() -> Self {
+        Appearance {
+            color: "#e0861a",
+            hat: None,
+        }
+    }
+}
+
+fn hat(hat: Option<Hat>, facing: Facing) -> Html<Model> {
+    let brim = match facing {
+        Facing::Down | Facing::Up => "M -9 -25 L 9 -25",
+        Facing::Right => "M -7 -25 L 13 -25",
+        Facing::Left => "M -13 -25 L 7 -25",
+    };
+    match hat {
+        Some(Hat::Cap) => html! {
+            <g class="hat cap",>
+                <path d="M -7 -25 A 7 6 0 0 1 7 -25 Z",></path>
+                <path class="brim", d=brim,></path>
+            </g>
+        },
+        Some(Hat::Wizard) => html! {
+            <path class="hat wizard", d="M -9 -24 L 0 -45 L 9 -24 Z",></path>
+        },
+        Some(Hat::Crown) => html! {
+            <path class="hat crown", d="M -7 -25 L -7 -33 L -3 -29 L 0 -35 L 3 -29 L 7 -33 L 7 -25 Z",></path>
+        },
+        None => html! { <g></g> },
+    }
+}
+
+/// Draws the player standing on the origin. While `walking` the legs swing and the body bobs in
+/// time with `ctx.wave`.
+pub fn render_avatar(appearance: &Appearance, facing: Facing, walking: bool, ctx: &Context) -> Html<Model> {
+    let swing = if walking { ctx.wave(TAU / 12.0) } else { 0.0 };
+    let bob = swing.abs() * 2.0;
+    let sideways = facing == Facing::Left || facing == Facing::Right;
+    let (legs, eyes) = if sideways {
+        ([(-2.0, 5.0 * swing, 10.0), (2.0, -5.0 * swing, 10.0)], vec![4.0])
+    } else {
+        ([(-4.0, 0.0, 10.0 + 2.0 * swing), (4.0, 0.0, 10.0 - 2.0 * swing)], match facing {
+            Facing::Down => vec![-3.0, 3.0],
+            _ => vec![],
+        })
+    };
+    let flip = if facing == Facing::Left { "scale(-1, 1)" } else { "" };
+    html! {
+        <g class="avatar", transform=flip,>
+            <ellipse class="shadow", cx=0, cy=10, rx=9, ry=3,></ellipse>
+            {for legs.iter().map(|&(x, dx, y)| {
+                html! { <line class="leg", x1=x, y1=0, x2={x + dx}, y2=y,></line> }
+            })}
+            <g transform={format!("translate(0, {})", -bob)},>
+                <ellipse class="body", cx=0, cy=-6, rx=9, ry=10, style={format!("fill: {}", appearance.color)},></ellipse>
+                <circle class="head", cx=0, cy=-20, r=7,></circle>
+                {for eyes.iter().map(|&x| {
+                    html! { <circle class="eye", cx=x, cy=-21, r=1.5,></circle> }
+                })}
+                { hat(appearance.hat, if facing == Facing::Left { Facing::Right } else { facing }) }
+            </g>
+        </g>
+    }
+}
+
+/// Picks a color and hat from the cosmetics the player has unlocked.
+pub struct Creator {
+    pub land: Overland,
+    pub row: usize,
+}
+
+impl Creator {
+    pub fn new(land: Overland) -> Self {
+        Creator {
+            land: land,
+            row: 0,
+        }
+    }
+    pub fn up(&mut self) {
+        self.row = 0;
+    }
+    pub fn down(&mut self) {
+        self.row = 1;
+    }
+    /// Steps the selected row `by` options through what the player owns.
+    pub fn cycle(&mut self, by: isize) {
+        let player = &mut self.land.player;
+        if self.row == 0 {
+            let colors: Vec<&'static str> = player.unlocked.iter().filter_map(|c| match c {
+                Cosmetic::Color(color) => Some(*color),
+                _ => None,
+            }).collect();
+            player.appearance.color = step(&colors, player.appearance.color, by);
+        } else {
+            let mut hats: Vec<Option<Hat>> = vec![None];
+            hats.extend(player.unlocked.iter().filter_map(|c| match c {
+                Cosmetic::Hat(hat) => Some(Some(*hat)),
+                _ => None,
+            }));
+            player.appearance.hat = step(&hats, player.appearance.hat, by);
+        }
+    }
+    pub fn render(&self, ctx: &Context) -> Html<Model> {
+        let player = &self.land.player;
+        let hat_name = match player.appearance.hat {
+            None => "No hat",
+            Some(Hat::Cap) => "Cap",
+            Some(Hat::Wizard) => "Wizard Hat",
+            Some(Hat::Crown) => "Crown",
+        };
+        let facing = [Facing::Down, Facing::Right, Facing::Up, Facing::Left][(ctx.time() / 2.0) as usize % 4];
+        html! {
+            <g class="creator",>
+                <rect class="problem_card", x=250, y=20, width=500, height=500, rx=10, ry=10,></rect>
+                <text class="title", x=500, y=80,>{"Your Look"}</text>
+                <g transform="translate(500, 250) scale(5)",>
+                    { render_avatar(&player.appearance, facing, true, ctx) }
+                </g>
+                <g class={if self.row == 0 { "option selected" } else { "option" }},>
+                    <rect x=300, y=352, width=400, height=40, rx=5, ry=5,></rect>
+                    <text x=500, y=380,>{ "◀ Color ▶" }</text>
+                </g>
+                <g class={if self.row == 1 { "option selected" } else { "option" }},>
+                    <rect x=300, y=402, width=400, height=40, rx=5, ry=5,></rect>
+                    <text x=500, y=430,>{ format!("◀ {} ▶", hat_name) }</text>
+                </g>
+                <text class="message", x=500, y=490,>{"Arrows to choose, Enter when done"}</text>
+            </g>
+        }
+    }
+}
+
+/// Steps `current` `by` places through `options`, wrapping around.
+pub fn step<T: Copy + PartialEq>(options: &[T], current: T, by: isize) -> T {
+    if options.is_empty() {
+        return current;
+    }
+    let i = options.iter().position(|&o| o == current).unwrap_or(0) as isize;
+    let len = options.len() as isize;
+    options[(((i + by) % len + len) % len) as usize]
+}
@@ -0,0 +1,249 @@
+use yew::{html, Html};
+use rand::prelude::*;
+
+use crate::problem::*;
+use crate::enemy::*;
+use crate::context::*;
+use crate::anim::TAU;
+use crate::boss::*;
+use crate::Model;
+
+/// Every enemy type a map file or encounter table can name, by id.
+pub const SPECIES: &[(&str, fn(usize) -> Box<Enemy>)] = &[
+    ("orb", spawn_orb),
+    ("beast", spawn_beast),
+    ("slime", spawn_slime),
+    ("golem", spawn_golem),
+    ("calculator", spawn_calculator),
+];
+
+pub fn spawn_enemy(id: &str, level: usize) -> Option<Box<Enemy>> {
+    SPECIES.iter().find(|(name, _)| *name == id).map(|(_, spawn)| spawn(level))
+}
+
+fn spawn_orb(level: usize) -> Box<Enemy> {
+    Box::new(Orb::new(level))
+}
+
+fn spawn_beast(level: usize) -> Box<Enemy> {
+    Box::new(MultiplierBeast::new(level))
+}
+
+fn spawn_slime(level: usize) -> Box<Enemy> {
+    Box::new(FractionSlime::new(level))
+}
+
+fn spawn_golem(level: usize) -> Box<Enemy> {
+    Box::new(ClockGolem::new(level))
+}
+
+fn spawn_calculator(level: usize) -> Box<Enemy> {
+    Box::new(grand_calculator(level))
+}
+
+pub struct BeastCard();
+
+impl EnemyCard for BeastCard {
+    fn render(&self, ctx: &Context) -> Html<Model> {
+        let stomp = ctx.wave(TAU / 3.0).abs() * 15.0;
+        html!{
+            <g class="circle_card beast_card",>
+                { shadow(160.0, 0.0, 0.0) }
+                <g transform={format!("translate(0, {})", -stomp)},>
+                    <path d="M -70 -10 L -80 -110 L -50 -90 L -30 -150 L 0 -120 L 30 -150 L 50 -90 L 80 -110 L 70 -10 Z",></path>
+                    <line x1=-25, y1=-75, x2=-5, y2=-55,></line>
+                    <line x1=-25, y1=-55, x2=-5, y2=-75,></line>
+                    <line x1=5, y1=-75, x2=25, y2=-55,></line>
+                    <line x1=5, y1=-55, x2=25, y2=-75,></line>
+                </g>
+                {damage_box(ctx)}
+            </g>
+        }
+    }
+}
+
+/// Takes multiplication problems and has a thick hide: eight hits to bring down.
+pub struct MultiplierBeast {
+    state: FighterState,
+    level: usize,
+}
+
+impl MultiplierBeast {
+    pub fn new(level: usize) -> Self {
+        MultiplierBeast {
+            level: level,
+            state: FighterState {
+                health: 1.0
+            }
+        }
+    }
+}
+
+impl Enemy for MultiplierBeast {
+    fn get_state(&self) -> FighterState {
+        self.state.clone()
+    }
+    fn get_properties(&self) -> EnemyProps {
+        EnemyProps {
+            level: format!("{}", self.level),
+            class: "beast".into(),
+            name: "Multiplier".into(),
+            card: Box::new(BeastCard()),
+        }
+    }
+    fn damage(&mut self, amount: f64) {
+        self.state.health -= 0.125*amount;
+    }
+    fn act(&mut self, _delta: f64) -> Option<EnemyAction> {
+        None
+    }
+    fn generate_problem(&mut self, rng: &mut SmallRng) -> Box<Problem> {
+        Box::new(gen_multiplication(rng, (self.level + 3).min(12)))
+    }
+}
+
+pub struct SlimeCard();
+
+impl EnemyCard for SlimeCard {
+    fn render(&self, ctx: &Context) -> Html<Model> {
+        let squish = ctx.wave(TAU / 2.0) * 0.15;
+        let (rx, ry) = (80.0 * (1.0 + squish), 60.0 * (1.0 - squish));
+        html!{
+            <g class="circle_card slime_card",>
+                { shadow(2.0*rx, 0.0, 0.0) }
+                <ellipse cx=0, cy={-ry}, rx=rx, ry=ry,></ellipse>
+                <line x1={-rx*0.6}, y1={-ry}, x2={rx*0.6}, y2={-ry},></line>
+                <circle cx=0, cy={-ry*1.45}, r=8,></circle>
+                <circle cx=0, cy={-ry*0.55}, r=8,></circle>
+                {damage_box(ctx)}
+            </g>
+        }
+    }
+}
+
+/// Asks fraction sums and slowly knits itself back together between hits.
+pub struct FractionSlime {
+    state: FighterState,
+    level: usize,
+}
+
+impl FractionSlime {
+    pub fn new(level: usize) -> Self {
+        FractionSlime {
+            level: level,
+            state: FighterState {
+                health: 1.0
+            }
+        }
+    }
+}
+
+impl Enemy for FractionSlime {
+    fn get_state(&self) -> FighterState {
+        self.state.clone()
+    }
+    fn get_properties(&self) -> EnemyProps {
+        EnemyProps {
+            level: format!("{}", self.level),
+            class: "gelatinous".into(),
+            name: "Fraction Slime".into(),
+            card: Box::new(SlimeCard()),
+        }
+    }
+    fn damage(&mut self, amount: f64) {
+        self.state.health -= 0.3*amount;
+    }
+    fn act(&mut self, delta: f64) -> Option<EnemyAction> {
+        if self.state.health > 0.0 {
+            self.state.health = (self.state.health + 0.01*delta).min(1.0);
+        }
+        None
+    }
+    fn generate_problem(&mut self, rng: &mut SmallRng) -> Box<Problem> {
+        Box::new(gen_fraction_add(rng, self.level))
+    }
+}
+
+pub struct GolemCard();
+
+impl EnemyCard for GolemCard {
+    fn render(&self, ctx: &Context) -> Html<Model> {
+        let hand = ctx.time() * 2.0;
+        html!{
+            <g class="circle_card golem_card",>
+                { shadow(140.0, 0.0, 0.0) }
+                <rect x=-60, y=-180, width=120, height=180, rx=10, ry=10,></rect>
+                <circle cx=0, cy=-110, r=45,></circle>
+                <line x1=0, y1=-110, x2={hand.sin()*35.0}, y2={-110.0-hand.cos()*35.0},></line>
+                <line x1=0, y1=-110, x2={(hand/12.0).sin()*22.0}, y2={-110.0-(hand/12.0).cos()*22.0},></line>
+                {damage_box(ctx)}
+            </g>
+        }
+    }
+}
+
+/// Asks the time and strikes on a fixed beat however quickly it is answered.
+pub struct ClockGolem {
+    state: FighterState,
+    level: usize,
+    wind: f64,
+}
+
+impl ClockGolem {
+    pub fn new(level: usize) -> Self {
+        ClockGolem {
+            level: level,
+            wind: 0.0,
+            state: FighterState {
+                health: 1.0
+            }
+        }
+    }
+}
+
+impl Enemy for ClockGolem {
+    fn get_state(&self) -> FighterState {
+        self.state.clone()
+    }
+    fn get_properties(&self) -> EnemyProps {
+        EnemyProps {
+            level: format!("{}", self.level),
+            class: "construct".into(),
+            name: "Clock Golem".into(),
+            card: Box::new(GolemCard()),
+        }
+    }
+    fn damage(&mut self, amount: f64) {
+        self.state.health -= 0.25*amount;
+    }
+    fn act(&mut self, delta: f64) -> Option<EnemyAction> {
+        self.wind += delta;
+        if self.wind >= 20.0 {
+            self.wind -= 20.0;
+            Some(EnemyAction::Attack(0.1))
+        } else {
+            None
+        }
+    }
+    fn generate_problem(&mut self, rng: &mut SmallRng) -> Box<Problem> {
+        Box::new(gen_clock(rng, self.level))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cards_hold_still_without_animations() {
+        let mut ctx = Context::new();
+        ctx.animations = false;
+        for (id, spawn) in SPECIES {
+            let card = spawn(1).get_properties().card;
+            ctx.anim_t = 0.0;
+            let before = card.render(&ctx);
+            ctx.anim_t = 1.7;
+            assert!(card.render(&ctx) == before, "{} moved with animations off", id);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use serde_derive::{Deserialize, Serialize};
+use yew::{html, Html};
+
+use crate::items::*;
+use crate::{Model, State};
+
+/// What a key press means to the game, whichever key it was.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Submit,
+    Back,
+    Hint,
+    /// Uses the item at this index of `Item::ALL`.
+    Item(usize),
+    Pause,
+    /// Opens the controls screen from the title screen.
+    Controls,
+    /// Opens the character creator from the shop.
+    Look,
+}
+
+impl Action {
+    pub const ALL: [Action; 14] = [
+        Action::Up, Action::Down, Action::Left, Action::Right, Action::Submit, Action::Back,
+        Action::Hint, Action::Item(0), Action::Item(1), Action::Item(2), Action::Item(3), Action::Pause,
+        Action::Controls, Action::Look,
+    ];
+
+    pub fn name(&self) -> String {
+        match self {
+            Action::Up => "Move up".into(),
+            Action::Down => "Move down".into(),
+            Action::Left => "Move left".into(),
+            Action::Right => "Move right".into(),
+            Action::Submit => "Answer / choose".into(),
+            Action::Back => "Back".into(),
+            Action::Hint => "Hint".into(),
+            Action::Item(i) => format!("Use {}", Item::ALL[*i].name()),
+            Action::Pause => "Pause".into(),
+            Action::Controls => "Controls (title screen)".into(),
+            Action::Look => "Change look (shop)".into(),
+        }
+    }
+    /// The item this action uses in battle, counting the hint as a hint token.
+    pub fn item(&self) -> Option<Item> {
+        match self {
+            Action::Item(i) => Item::ALL.get(*i).cloned(),
+            Action::Hint => Some(Item::HintToken),
+            _ => None,
+        }
+    }
+}
+
+/// Keys the on-screen pads and gamepads press. They keep their usual meaning unless the player
+/// binds them to something else.
+const FALLBACK: &[(&str, Action)] = &[
+    ("ArrowUp", Action::Up),
+    ("ArrowDown", Action::Down),
+    ("ArrowLeft", Action::Left),
+    ("ArrowRight", Action::Right),
+    ("Enter", Action::Submit),
+    ("Escape", Action::Back),
+];
+
+/// Which physical keys, as reported by `KeyboardEvent.key`, trigger which actions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bindings {
+    pub keys: Vec<(String, Action)>,
+}
+
+impl Bindings {
+    fn from_table(table: &[(&str, Action)]) -> Self {
+        Bindings {
+            keys: table.iter().map(|&(key, action)| (key.to_string(), action)).collect(),
+        }
+    }
+    pub fn arrows() -> Self {
+        Bindings::from_table(&[
+            ("ArrowUp", Action::Up),
+            ("ArrowDown", Action::Down),
+            ("ArrowLeft", Action::Left),
+            ("ArrowRight", Action::Right),
+            ("Enter", Action::Submit),
+            ("Escape", Action::Back),
+            ("?", Action::Hint),
+            ("F1", Action::Item(0)),
+            ("F2", Action::Item(1)),
+            ("F3", Action::Item(2)),
+            ("F4", Action::Item(3)),
+            ("p", Action::Pause),
+            ("Pause", Action::Pause),
+            ("k", Action::Controls),
+            ("c", Action::Look),
+        ])
+    }
+    pub fn wasd() -> Self {
+        let mut bindings = Bindings::arrows();
+        for (key, action) in bindings.keys.iter_mut() {
+            *key = match action {
+                Action::Up => "w".into(),
+                Action::Down => "s".into(),
+                Action::Left => "a".into(),
+                Action::Right => "d".into(),
+                _ => continue,
+            };
+        }
+        bindings.keys.push((" ".into(), Action::Submit));
+        bindings
+    }
+    pub fn action(&self, key: &str) -> Option<Action> {
+        self.keys.iter().find(|(k, _)| k == key).map(|&(_, action)| action)
+            .or_else(|| FALLBACK.iter().find(|&&(k, _)| k == key).map(|&(_, action)| action))
+    }
+    pub fn keys_for(&self, action: Action) -> Vec<&str> {
+        self.keys.iter().filter(|&&(_, a)| a == action).map(|(k, _)| k.as_str()).collect()
+    }
+    /// Every key bound to an item.
+    pub fn item_keys(&self) -> Vec<String> {
+        (0..Item::ALL.len()).flat_map(|i| self.keys_for(Action::Item(i))).map(String::from).collect()
+    }
+    /// Makes `key` the only key for `action`, taking it away from whatever it did before.
+    pub fn bind(&mut self, action: Action, key: &str) {
+        self.keys.retain(|(k, a)| *a != action && k != key);
+        self.keys.push((key.into(), action));
+    }
+}
+
+/// How a key is shown on the bindings screen.
+pub fn key_label(key: &str) -> &str {
+    match key {
+        " " => "Space",
+        "ArrowUp" => "↑",
+        "ArrowDown" => "↓",
+        "ArrowLeft" => "←",
+        "ArrowRight" => "→",
+        k => k,
+    }
+}
+
+/// The screen for rebinding keys. The first row switches between the arrow and WASD presets;
+/// choosing any other row waits for the next key press and binds it to that row's action.
+pub struct BindingsScreen {
+    pub back: Box<State>,
+    pub row: usize,
+    pub waiting: bool,
+}
+
+impl BindingsScreen {
+    pub fn new(back: State) -> Self {
+        BindingsScreen {
+            back: Box::new(back),
+            row: 0,
+            waiting: false,
+        }
+    }
+    /// Handles a key press. Returns true once the player backs out of the screen.
+    pub fn key(&mut self, key: &str, bindings: &mut Bindings) -> bool {
+        if self.waiting {
+            self.waiting = false;
+            if key != "Escape" {
+                bindings.bind(Action::ALL[self.row - 1], key);
+            }
+            return false;
+        }
+        match bindings.action(key) {
+            Some(Action::Up) => self.row = (self.row + Action::ALL.len()) % (Action::ALL.len() + 1),
+            Some(Action::Down) => self.row = (self.row + 1) % (Action::ALL.len() + 1),
+            Some(Action::Submit) | Some(Action::Left) | Some(Action::Right) if self.row == 0 => {
+                *bindings = if *bindings == Bindings::arrows() { Bindings::wasd() } else { Bindings::arrows() };
+            },
+            Some(Action::Submit) => self.waiting = true,
+            Some(Action::Back) => return true,
+            _ => (),
+        }
+        false
+    }
+    pub fn render(&self, bindings: &Bindings) -> Html<Model> {
+        let preset = if *bindings == Bindings::arrows() {
+            "Arrows"
+        } else if *bindings == Bindings::wasd() {
+            "WASD"
+        } else {
+            "Custom"
+        };
+        let row_class = |row: usize| if row == self.row { "option selected" } else { "option" };
+        html! {
+            <g class="bindings",>
+                <rect class="problem_card", x=200, y=10, width=600, height=580, rx=10, ry=10,></rect>
+                <text class="title", x=500, y=55,>{"Controls"}</text>
+                <g class={row_class(0)},>
+                    <rect x=230, y=72, width=540, height=28, rx=5, ry=5,></rect>
+                    <text x=500, y=93,>{ format!("◀ Preset: {} ▶", preset) }</text>
+                </g>
+                {for Action::ALL.iter().enumerate().map(|(i, action)| {
+                    let y = 104 + i as i32 * 31;
+                    let keys = if self.waiting && self.row == i + 1 {
+                        "press a key…".to_string()
+                    } else {
+                        bindings.keys_for(*action).iter().map(|k| key_label(k)).collect::<Vec<_>>().join(", ")
+                    };
+                    html! {
+                        <g class={row_class(i + 1)},>
+                            <rect x=230, y=y, width=540, height=28, rx=5, ry=5,></rect>
+                            <text class="action", x=250, y={y + 21},>{ action.name() }</text>
+                            <text class="keys", x=750, y={y + 21},>{ keys }</text>
+                        </g>
+                    }
+                })}
+                <text class="message", x=500, y=570,>{"Enter to rebind, Escape when done"}</text>
+            </g>
+        }
+    }
+}
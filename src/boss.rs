@@ -0,0 +1,165 @@
+use yew::{html, Html};
+use rand::prelude::*;
+
+use crate::problem::*;
+use crate::enemy::*;
+use crate::context::*;
+use crate::anim::TAU;
+use crate::Model;
+
+/// One stage of a boss fight. The phase lasts while the boss' health is above `until`.
+pub struct Phase {
+    pub until: f64,
+    pub name: &'static str,
+    pub card: fn() -> Box<EnemyCard>,
+    pub problem: fn(&mut SmallRng, usize) -> Box<Problem>,
+    /// Health lost per correct answer.
+    pub hit: f64,
+    /// Seconds between attacks and the damage each one does.
+    pub attack: Option<(f64, f64)>,
+}
+
+pub struct Boss {
+    state: FighterState,
+    level: usize,
+    class: &'static str,
+    phases: Vec<Phase>,
+    phase: usize,
+    timer: f64,
+}
+
+impl Boss {
+    pub fn new(level: usize, class: &'static str, phases: Vec<Phase>) -> Self {
+        Boss {
+            state: FighterState {
+                health: 1.0
+            },
+            level: level,
+            class: class,
+            phases: phases,
+            phase: 0,
+            timer: 0.0,
+        }
+    }
+    fn current(&self) -> &Phase {
+        &self.phases[self.phase]
+    }
+}
+
+impl Enemy for Boss {
+    fn get_state(&self) -> FighterState {
+        self.state.clone()
+    }
+    fn get_properties(&self) -> EnemyProps {
+        let phase = self.current();
+        EnemyProps {
+            level: format!("{}", self.level),
+            class: self.class.into(),
+            name: phase.name.into(),
+            card: (phase.card)(),
+        }
+    }
+    fn damage(&mut self, amount: f64) {
+        self.state.health -= self.current().hit*amount;
+        while self.phase + 1 < self.phases.len() && self.state.health <= self.current().until {
+            self.phase += 1;
+            self.timer = 0.0;
+        }
+    }
+    fn act(&mut self, delta: f64) -> Option<EnemyAction> {
+        let (period, amount) = self.current().attack?;
+        self.timer += delta;
+        if self.timer >= period {
+            self.timer -= period;
+            Some(EnemyAction::Attack(amount))
+        } else {
+            None
+        }
+    }
+    fn generate_problem(&mut self, rng: &mut SmallRng) -> Box<Problem> {
+        (self.current().problem)(rng, self.level)
+    }
+    fn boss_phases(&self) -> Option<Vec<f64>> {
+        Some(self.phases.iter().take(self.phases.len() - 1).map(|p| p.until).collect())
+    }
+}
+
+/// The calculator's display gets angrier with each phase.
+pub struct CalculatorCard(usize);
+
+impl EnemyCard for CalculatorCard {
+    fn render(&self, ctx: &Context) -> Html<Model> {
+        let shake = if self.0 >= 2 { ctx.wave(TAU / 40.0) * 3.0 } else { 0.0 };
+        let float = ctx.wave(TAU) * 10.0 * (self.0 as f32 + 1.0);
+        let display = ["0", "±", "÷0"][self.0.min(2)];
+        html!{
+            <g class={format!("circle_card calculator_card phase{}", self.0)},>
+                { shadow(180.0, 0.0, 0.0) }
+                <g transform={format!("translate({}, {})", shake, -float)},>
+                    <rect x=-80, y=-260, width=160, height=230, rx=15, ry=15,></rect>
+                    <rect class="display", x=-60, y=-240, width=120, height=50,></rect>
+                    <text class="display_text", x=50, y=-200,>{ display }</text>
+                    {for (0..9).map(|i| {
+                        html! {
+                            <rect class="key", x={-60 + (i % 3) * 45}, y={-175 + (i / 3) * 45}, width=30, height=30, rx=5, ry=5,></rect>
+                        }
+                    })}
+                </g>
+                {damage_box(ctx)}
+            </g>
+        }
+    }
+}
+
+fn calculator_card_0() -> Box<EnemyCard> {
+    Box::new(CalculatorCard(0))
+}
+
+fn calculator_card_1() -> Box<EnemyCard> {
+    Box::new(CalculatorCard(1))
+}
+
+fn calculator_card_2() -> Box<EnemyCard> {
+    Box::new(CalculatorCard(2))
+}
+
+fn calculator_sums(rng: &mut SmallRng, level: usize) -> Box<Problem> {
+    Box::new(gen_simple_add_sub(rng, level*5, level*20))
+}
+
+fn calculator_products(rng: &mut SmallRng, level: usize) -> Box<Problem> {
+    Box::new(gen_multiplication(rng, (level + 4).min(12)))
+}
+
+fn calculator_fractions(rng: &mut SmallRng, level: usize) -> Box<Problem> {
+    Box::new(gen_fraction_add(rng, level))
+}
+
+pub fn grand_calculator(level: usize) -> Boss {
+    Boss::new(level, "boss", vec![
+        Phase {
+            until: 0.66,
+            name: "Grand Calculator",
+            card: calculator_card_0,
+            problem: calculator_sums,
+            hit: 0.12,
+            attack: None,
+        },
+        Phase {
+            until: 0.33,
+            name: "Calculator Unbound",
+            card: calculator_card_1,
+            problem: calculator_products,
+            hit: 0.1,
+            attack: Some((15.0, 0.1)),
+        },
+        Phase {
+            until: 0.0,
+            name: "Calculator Overclocked",
+            card: calculator_card_2,
+            problem: calculator_fractions,
+            hit: 0.1,
+            attack: Some((8.0, 0.15)),
+        },
+    ])
+}
@@ -0,0 +1,59 @@
+/// Answers given within this many seconds of the problem appearing count as fast.
+const FAST_ANSWER: f32 = 5.0;
+const MAX_MULTIPLIER: f64 = 2.5;
+
+/// Tracks the player's run of correct answers in a battle and the special attack meter it fills.
+pub struct Combo {
+    pub streak: usize,
+    pub meter: f32,
+    /// Seconds since the current problem appeared.
+    pub timer: f32,
+}
+
+pub struct Strike {
+    pub multiplier: f64,
+    pub fast: bool,
+    pub special: bool,
+}
+
+impl Combo {
+    pub fn new() -> Self {
+        Combo {
+            streak: 0,
+            meter: 0.0,
+            timer: 0.0,
+        }
+    }
+    pub fn tick(&mut self, delta: f32) {
+        self.timer += delta;
+    }
+    /// Registers a correct answer. Every answer in the streak after the first adds a quarter to
+    /// the damage multiplier, a fast answer adds another quarter, and once the meter is full the
+    /// answer lands as a special attack doing double damage.
+    pub fn hit(&mut self) -> Strike {
+        let fast = self.timer < FAST_ANSWER;
+        self.streak += 1;
+        self.timer = 0.0;
+        let mut multiplier = 1.0 + 0.25 * (self.streak - 1) as f64;
+        if fast {
+            multiplier += 0.25;
+        }
+        let special = self.meter >= 1.0;
+        if special {
+            self.meter = 0.0;
+        } else {
+            self.meter = (self.meter + if fast { 0.25 } else { 0.15 }).min(1.0);
+        }
+        let multiplier = multiplier.min(MAX_MULTIPLIER);
+        Strike {
+            multiplier: if special { multiplier * 2.0 } else { multiplier },
+            fast: fast,
+            special: special,
+        }
+    }
+    pub fn miss(&mut self) {
+        self.streak = 0;
+        self.meter = 0.0;
+        self.timer = 0.0;
+    }
+}
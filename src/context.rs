@@ -1,4 +1,50 @@
+use crate::anim::*;
+
+/// The animation clock shared by everything that draws. Looping motion reads `anim_t` through
+/// `wave`; one-shot reactions are started with `trigger` and sampled with `value`. With
+/// `animations` off both hold still at their resting values.
 pub struct Context {
     pub anim_t: f32,
-    pub time_damage: f32,
+    pub animations: bool,
+    started: Vec<(Event, f32)>,
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Context {
+            anim_t: 0.0,
+            animations: true,
+            started: Vec::new(),
+        }
+    }
+    pub fn tick(&mut self, delta: f32) {
+        self.anim_t += delta;
+    }
+    /// Starts `event` over from the beginning of its timeline.
+    pub fn trigger(&mut self, event: Event) {
+        let now = self.anim_t;
+        self.started.retain(|&(e, _)| e != event);
+        self.started.push((event, now));
+    }
+    /// Seconds since `event` was last triggered.
+    pub fn since(&self, event: Event) -> Option<f32> {
+        self.started.iter().find(|&&(e, _)| e == event).map(|&(_, t)| self.anim_t - t)
+    }
+    pub fn running(&self, event: Event) -> bool {
+        self.since(event).map_or(false, |t| t < event.timeline().length())
+    }
+    /// The current value of `event`'s timeline, or its resting value when it isn't playing.
+    pub fn value(&self, event: Event) -> f32 {
+        let timeline = event.timeline();
+        let t = self.since(event).filter(|_| self.animations);
+        timeline.at(t.unwrap_or_else(|| timeline.length()))
+    }
+    /// The clock for motion that runs on without looping back, such as spinning. Stays at zero
+    /// with `animations` off.
+    pub fn time(&self) -> f32 {
+        if self.animations { self.anim_t } else { 0.0 }
+    }
+    pub fn wave(&self, period: f32) -> f32 {
+        if self.animations { wave(self.anim_t, period) } else { 0.0 }
+    }
 }
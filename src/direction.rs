@@ -1,10 +1,12 @@
-use yew::events::{KeyDownEvent, KeyUpEvent, IKeyboardEvent};
+use crate::bindings::*;
 
 pub struct Direction {
     pub up: bool,
     pub down: bool,
     pub left: bool,
     pub right: bool,
+    /// A gamepad stick, already past its deadzone. Overrides the keys while pushed.
+    pub stick: [f32; 2],
 }
 
 impl Direction {
@@ -14,24 +16,30 @@ impl Direction {
             down: false,
             left: false,
             right: false,
+            stick: [0.0, 0.0],
         }
     }
-    pub fn down(&mut self, evt: &KeyDownEvent) {
-        self.set(&evt.key(), true);
+    pub fn down(&mut self, action: Option<Action>) {
+        self.set(action, true);
     }
-    pub fn up(&mut self, evt: &KeyUpEvent) {
-        self.set(&evt.key(), false);
+    pub fn up(&mut self, action: Option<Action>) {
+        self.set(action, false);
     }
-    pub fn set(&mut self, code: &str, val: bool) {
-        match code {
-            "ArrowUp" => { self.up = val; },
-            "ArrowDown" => { self.down = val; },
-            "ArrowLeft" => { self.left = val; },
-            "ArrowRight" => { self.right = val; },
+    pub fn set(&mut self, action: Option<Action>, val: bool) {
+        match action {
+            Some(Action::Up) => { self.up = val; },
+            Some(Action::Down) => { self.down = val; },
+            Some(Action::Left) => { self.left = val; },
+            Some(Action::Right) => { self.right = val; },
             _ => ()
         }
     }
+    /// The way to walk, at most one long: the stick if it is pushed, otherwise the arrow keys
+    /// with diagonals kept as fast as straight lines.
     pub fn direction(&self) -> [f32; 2] {
+        if self.stick != [0.0, 0.0] {
+            return self.stick;
+        }
         let x = match (self.right, self.left) {
             (true, false) => 1.0,
             (false, true) => -1.0,
@@ -42,7 +50,11 @@ impl Direction {
             (false, true) => -1.0,
             _ => 0.0,
         };
-        [x, y]
+        if x != 0.0 && y != 0.0 {
+            [x * std::f32::consts::FRAC_1_SQRT_2, y * std::f32::consts::FRAC_1_SQRT_2]
+        } else {
+            [x, y]
+        }
     }
 }
 
@@ -0,0 +1,283 @@
+use cgmath::{Vector2, InnerSpace};
+use rand::prelude::*;
+
+use crate::enemy::*;
+use crate::bestiary::*;
+use crate::terrain::*;
+use crate::tilemap::*;
+
+/// Distance the player has to walk after a battle before another random one can start.
+const COOLDOWN: f32 = 300.0;
+const ROAM_SPEED: f32 = 40.0;
+const ROAM_TURN: f32 = 2.0;
+const ROAMER_REACH: f32 = 30.0;
+
+pub struct EncounterEntry {
+    pub enemy: String,
+    pub levels: (usize, usize),
+    pub weight: f32,
+}
+
+impl EncounterEntry {
+    /// Parses a table written as comma separated `id:level[:weight]` entries, where level is
+    /// either a single level or an inclusive range like `2-4`.
+    pub fn parse_table(table: &str) -> Result<Vec<Self>, String> {
+        table.split(',').map(|entry| entry.trim()).filter(|entry| !entry.is_empty()).map(|entry| {
+            let parts: Vec<&str> = entry.split(':').collect();
+            if parts.len() < 2 || parts.len() > 3 {
+                return Err(format!("bad encounter {:?}", entry));
+            }
+            let (enemy, levels, weight) = (parts[0], parts[1], parts.get(2).cloned().unwrap_or("1"));
+            let bad_level = |_| format!("bad level in encounter {:?}", entry);
+            let levels: (usize, usize) = match levels.find('-') {
+                Some(i) => (levels[..i].parse().map_err(bad_level)?, levels[i+1..].parse().map_err(bad_level)?),
+                None => {
+                    let level = levels.parse().map_err(bad_level)?;
+                    (level, level)
+                },
+            };
+            if levels.0 < 1 {
+                return Err(format!("bad level in encounter {:?}", entry));
+            }
+            if levels.0 > levels.1 {
+                return Err(format!("backwards level range in encounter {:?}", entry));
+            }
+            if spawn_enemy(enemy, levels.0).is_none() {
+                return Err(format!("unknown enemy {:?}", enemy));
+            }
+            let weight: f32 = weight.parse().map_err(|_| format!("bad weight in encounter {:?}", entry))?;
+            if weight <= 0.0 || !weight.is_finite() {
+                return Err(format!("bad weight in encounter {:?}", entry));
+            }
+            Ok(EncounterEntry {
+                enemy: enemy.to_string(),
+                levels: levels,
+                weight: weight,
+            })
+        }).collect()
+    }
+}
+
+/// An area of the map with its own encounter rate and enemy table. `rate` is the expected
+/// number of random battles per 1000 units walked; `roamers` is how many visible enemies wander
+/// the zone instead.
+pub struct EncounterZone {
+    pub area: Vec<Vector2<f32>>,
+    pub rate: f32,
+    pub roamers: usize,
+    pub table: Vec<EncounterEntry>,
+}
+
+impl EncounterZone {
+    pub fn from_object(object: &MapObject) -> Result<Self, String> {
+        let zone = EncounterZone {
+            area: object.points.clone(),
+            rate: object.property_f32("rate").unwrap_or(0.0),
+            roamers: object.property_f32("roamers").unwrap_or(0.0) as usize,
+            table: EncounterEntry::parse_table(object.property_str("table").unwrap_or(""))?,
+        };
+        match zone.bounds() {
+            Some((min, max)) if min.x < max.x && min.y < max.y => Ok(zone),
+            _ => Err(format!("encounter zone {:?} has no area", object.name)),
+        }
+    }
+    /// The corners of the box around the zone, or `None` if it has no points.
+    pub fn bounds(&self) -> Option<(Vector2<f32>, Vector2<f32>)> {
+        let first = *self.area.first()?;
+        Some(self.area.iter().fold((first, first), |(min, max), p| {
+            (Vector2::new(min.x.min(p.x), min.y.min(p.y)), Vector2::new(max.x.max(p.x), max.y.max(p.y)))
+        }))
+    }
+    pub fn spawn<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Box<Enemy>> {
+        let entry = self.table.choose_weighted(rng, |e| e.weight).ok()?;
+        let level = rng.gen_range(entry.levels.0, entry.levels.1 + 1);
+        spawn_enemy(&entry.enemy, level)
+    }
+}
+
+/// A visible enemy walking around its zone; touching it starts the battle.
+pub struct Roamer {
+    pub pos: Vector2<f32>,
+    pub heading: Vector2<f32>,
+    pub turn: f32,
+    pub zone: usize,
+    pub enemy: Box<Enemy>,
+    pub props: EnemyProps,
+}
+
+/// A guaranteed fight waiting at a fixed spot, read from the map's `bosses` object layer.
+pub struct BossSite {
+    pub area: Vec<Vector2<f32>>,
+    pub center: Vector2<f32>,
+    pub enemy: Box<Enemy>,
+    pub props: EnemyProps,
+}
+
+impl BossSite {
+    pub fn from_object(object: &MapObject) -> Result<Self, String> {
+        let id = object.property_str("enemy").ok_or_else(|| format!("boss {:?} has no enemy", object.name))?;
+        let level = object.property_f32("level").unwrap_or(1.0);
+        if level < 1.0 || level.fract() != 0.0 {
+            return Err(format!("bad level for boss {:?}", object.name));
+        }
+        let level = level as usize;
+        let enemy = spawn_enemy(id, level).ok_or_else(|| format!("unknown enemy {:?}", id))?;
+        Ok(BossSite {
+            area: object.points.clone(),
+            center: object.center(),
+            props: enemy.get_properties(),
+            enemy: enemy,
+        })
+    }
+}
+
+pub struct Encounters {
+    pub zones: Vec<EncounterZone>,
+    pub roamers: Vec<Roamer>,
+    pub bosses: Vec<BossSite>,
+    /// Distance walked since the last battle.
+    pub walked: f32,
+}
+
+impl Encounters {
+    /// Reads the zones from the map's `encounters` object layer.
+    pub fn from_map<R: Rng + ?Sized>(map: &TileMap, terrain: &Terrain, rng: &mut R) -> Result<Self, String> {
+        let zones = map.objects.iter()
+            .filter(|o| o.layer == "encounters")
+            .map(EncounterZone::from_object)
+            .collect::<Result<Vec<_>, _>>()?;
+        let bosses = map.objects.iter()
+            .filter(|o| o.layer == "bosses")
+            .map(BossSite::from_object)
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut roamers = Vec::new();
+        for (i, zone) in zones.iter().enumerate() {
+            let (min, max) = match zone.bounds() {
+                Some(bounds) => bounds,
+                None => continue,
+            };
+            for _ in 0..zone.roamers {
+                let mut pos = None;
+                for _ in 0..10 {
+                    let candidate = Vector2::new(rng.gen_range(min.x, max.x), rng.gen_range(min.y, max.y));
+                    if point_in_polygon(&zone.area, candidate) && !terrain.blocked(candidate, ROAMER_REACH) {
+                        pos = Some(candidate);
+                        break;
+                    }
+                }
+                if let (Some(pos), Some(enemy)) = (pos, zone.spawn(rng)) {
+                    roamers.push(Roamer {
+                        pos: pos,
+                        heading: Vector2::new(0.0, 0.0),
+                        turn: 0.0,
+                        zone: i,
+                        props: enemy.get_properties(),
+                        enemy: enemy,
+                    });
+                }
+            }
+        }
+        Ok(Encounters {
+            zones: zones,
+            roamers: roamers,
+            bosses: bosses,
+            walked: 0.0,
+        })
+    }
+    /// Hands back the boss whose site the player just stepped onto, once.
+    pub fn boss_at(&mut self, pos: Vector2<f32>) -> Option<Box<Enemy>> {
+        let i = self.bosses.iter().position(|site| point_in_polygon(&site.area, pos))?;
+        self.walked = 0.0;
+        Some(self.bosses.remove(i).enemy)
+    }
+    /// Rolls for a random battle after the player walked `distance` ending at `pos`.
+    pub fn step<R: Rng + ?Sized>(&mut self, pos: Vector2<f32>, distance: f32, rng: &mut R) -> Option<Box<Enemy>> {
+        self.walked += distance;
+        if self.walked < COOLDOWN {
+            return None;
+        }
+        let zone = self.zones.iter().find(|zone| point_in_polygon(&zone.area, pos))?;
+        if zone.rate * distance / 1000.0 > rng.gen_range(0.0, 1.0) {
+            self.walked = 0.0;
+            zone.spawn(rng)
+        } else {
+            None
+        }
+    }
+    /// Wanders the roamers around their zones and hands back the one the player walked into.
+    pub fn roam<R: Rng + ?Sized>(&mut self, player: Vector2<f32>, delta: f32, terrain: &Terrain, rng: &mut R) -> Option<Box<Enemy>> {
+        for roamer in self.roamers.iter_mut() {
+            roamer.turn -= delta;
+            if roamer.turn <= 0.0 {
+                let angle = rng.gen_range(0.0, std::f32::consts::PI * 2.0);
+                roamer.heading = Vector2::new(angle.cos(), angle.sin()) * rng.gen_range(0.0, 1.0);
+                roamer.turn = ROAM_TURN;
+            }
+            let next = roamer.pos + roamer.heading * (ROAM_SPEED * delta);
+            if point_in_polygon(&self.zones[roamer.zone].area, next) && !terrain.blocked(next, ROAMER_REACH) {
+                roamer.pos = next;
+            } else {
+                roamer.turn = 0.0;
+            }
+        }
+        let hit = self.roamers.iter().position(|r| (r.pos - player).magnitude() < ROAMER_REACH);
+        hit.map(|i| {
+            self.walked = 0.0;
+            self.roamers.swap_remove(i).enemy
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn boss(level: f32) -> MapObject {
+        let mut properties = HashMap::new();
+        properties.insert("enemy".to_string(), "golem".into());
+        properties.insert("level".to_string(), level.into());
+        MapObject {
+            layer: "bosses".into(),
+            name: "gate".into(),
+            kind: String::new(),
+            points: vec![Vector2::new(0.0, 0.0), Vector2::new(10.0, 0.0), Vector2::new(10.0, 10.0)],
+            properties: properties,
+        }
+    }
+
+    #[test]
+    fn table() {
+        let table = EncounterEntry::parse_table("orb:1, orb:2-4:0.5").unwrap();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table[1].levels, (2, 4));
+        assert_eq!(table[1].weight, 0.5);
+    }
+
+    #[test]
+    fn level_zero_is_rejected() {
+        assert!(EncounterEntry::parse_table("orb:0").is_err());
+        assert!(EncounterEntry::parse_table("orb:0-3").is_err());
+    }
+
+    #[test]
+    fn backwards_range_is_rejected() {
+        assert!(EncounterEntry::parse_table("orb:4-2").is_err());
+    }
+
+    #[test]
+    fn weight_must_be_positive() {
+        assert!(EncounterEntry::parse_table("orb:1:0").is_err());
+        assert!(EncounterEntry::parse_table("orb:1:-2").is_err());
+        assert!(EncounterEntry::parse_table("orb:1:NaN").is_err());
+    }
+
+    #[test]
+    fn boss_level_must_be_whole_and_positive() {
+        assert!(BossSite::from_object(&boss(3.0)).is_ok());
+        assert!(BossSite::from_object(&boss(0.0)).is_err());
+        assert!(BossSite::from_object(&boss(-2.0)).is_err());
+        assert!(BossSite::from_object(&boss(1.5)).is_err());
+    }
+}
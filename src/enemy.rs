@@ -2,6 +2,7 @@ use yew::{html, Html};
 use crate::problem::*;
 use crate::Model;
 use crate::context::*;
+use crate::anim::*;
 use rand::prelude::*;
 
 pub trait EnemyCard {
@@ -11,14 +12,14 @@ pub trait EnemyCard {
 #[derive(Debug, Copy, Clone)]
 pub struct CircleCard();
 
-fn shadow(size: f32, x: f32, y: f32) -> Html<Model> {
+pub fn shadow(size: f32, x: f32, y: f32) -> Html<Model> {
     html!{
         <ellipse class="shadow", cx=x, cy=y, rx={size/2.0}, ry={size/8.0},></ellipse>
     }
 }
 
-fn damage_box(ctx: &Context) -> Html<Model> {
-    if ctx.time_damage < 0.25 {
+pub fn damage_box(ctx: &Context) -> Html<Model> {
+    if ctx.value(Event::HitFlash) > 0.15 {
         let mut rng = SmallRng::from_entropy();
         let x = rng.gen_range(-40.0, 40.0);
         let y = rng.gen_range(-150.0, -50.0);
@@ -36,8 +37,8 @@ fn damage_box(ctx: &Context) -> Html<Model> {
 
 impl EnemyCard for CircleCard {
     fn render(&self, ctx: &Context) -> Html<Model> {
-        let float = ctx.anim_t.sin() * 0.1 + 1.0;
-        let unfloat = ctx.anim_t.sin() * (-0.1) + 1.0;
+        let float = ctx.wave(TAU) * 0.1 + 1.0;
+        let unfloat = ctx.wave(TAU) * (-0.1) + 1.0;
         html!{
             <g class="circle_card",>
             { shadow(100.0*unfloat, 0.0, 0.0) }
@@ -52,9 +53,9 @@ pub struct OrbCard();
 
 impl EnemyCard for OrbCard {
     fn render(&self, ctx: &Context) -> Html<Model> {
-        let float = ctx.anim_t.sin() * 0.1 + 1.0;
-        let unfloat = ctx.anim_t.sin() * (-0.1) + 1.0;
-        let rot = ctx.anim_t*1.0;
+        let float = ctx.wave(TAU) * 0.1 + 1.0;
+        let unfloat = ctx.wave(TAU) * (-0.1) + 1.0;
+        let rot = ctx.time();
         html!{
             <g class="circle_card",>
             { shadow(100.0*unfloat, 0.0, 0.0) }
@@ -90,7 +91,11 @@ pub trait Enemy {
     fn get_properties(&self) -> EnemyProps;
     fn damage(&mut self, amount: f64) -> ();
     fn act(&mut self, delta: f64) -> Option<EnemyAction>;
-    fn generate_problem(&mut self) -> Box<Problem>;
+    fn generate_problem(&mut self, rng: &mut SmallRng) -> Box<Problem>;
+    /// Health thresholds between a boss' phases; `None` for ordinary enemies.
+    fn boss_phases(&self) -> Option<Vec<f64>> {
+        None
+    }
 }
 
 
@@ -132,7 +137,7 @@ impl Enemy for Orb {
     fn act(&mut self, _delta: f64) -> Option<EnemyAction> {
         None
     }
-    fn generate_problem(&mut self) -> Box<Problem> {
-        Box::new(gen_simple_add_sub(self.level*2, self.level*8))
+    fn generate_problem(&mut self, rng: &mut SmallRng) -> Box<Problem> {
+        Box::new(gen_simple_add_sub(rng, self.level*2, self.level*8))
     }
 }
@@ -0,0 +1,61 @@
+use yew::{html, Html};
+
+use crate::anim::*;
+use crate::context::Context;
+use crate::Model;
+
+/// How long a line of combat text stays on screen.
+const LIFE: f32 = 1.2;
+/// How far it rises over that time.
+const RISE: f32 = 60.0;
+
+struct FloatingText {
+    text: String,
+    class: &'static str,
+    pos: [f32; 2],
+    age: f32,
+}
+
+/// Short lines of feedback in battle, such as damage dealt or the answer to a missed problem,
+/// that drift upward and fade out.
+pub struct FloatingTexts {
+    texts: Vec<FloatingText>,
+}
+
+impl FloatingTexts {
+    pub fn new() -> Self {
+        FloatingTexts {
+            texts: Vec::new(),
+        }
+    }
+    /// Shows `text` starting at `pos`, styled by the CSS class `class`.
+    pub fn add<S: Into<String>>(&mut self, text: S, pos: [f32; 2], class: &'static str) {
+        self.texts.push(FloatingText {
+            text: text.into(),
+            class: class,
+            pos: pos,
+            age: 0.0,
+        });
+    }
+    pub fn tick(&mut self, delta: f32) {
+        for text in self.texts.iter_mut() {
+            text.age += delta;
+        }
+        self.texts.retain(|t| t.age < LIFE);
+    }
+    /// Draws the texts, holding them in place instead of rising with `animations` off.
+    pub fn render(&self, ctx: &Context) -> Html<Model> {
+        let rise = Tween { from: 0.0, to: RISE, duration: LIFE, ease: Ease::OutQuad };
+        let fade = Timeline::new(1.0).hold(LIFE / 2.0).to(0.0, LIFE / 2.0, Ease::InQuad);
+        html! {
+            <g class="floating_texts",>
+            {for self.texts.iter().map(|t| {
+                html! {
+                    <text class={format!("floating {}", t.class)}, x={t.pos[0]}, y={t.pos[1] - if ctx.animations { rise.at(t.age) } else { 0.0 }},
+                        style={format!("opacity: {}", fade.at(t.age))},>{ &t.text }</text>
+                }
+            })}
+            </g>
+        }
+    }
+}
@@ -0,0 +1,37 @@
+/// Length of one simulation step in seconds.
+pub const STEP: f32 = 1.0 / 60.0;
+
+/// Longest stretch of real time a single frame may account for. Browsers stop delivering
+/// animation frames to background tabs, so the first frame after resuming can be minutes late.
+const MAX_FRAME: f32 = 0.25;
+
+/// Turns requestAnimationFrame timestamps into a whole number of fixed simulation steps,
+/// carrying the remainder over to the next frame.
+pub struct FrameClock {
+    last: Option<f64>,
+    accumulator: f32,
+}
+
+impl FrameClock {
+    pub fn new() -> Self {
+        FrameClock {
+            last: None,
+            accumulator: 0.0,
+        }
+    }
+    /// Takes a frame timestamp in milliseconds and returns the number of steps to simulate.
+    pub fn frame(&mut self, timestamp: f64) -> usize {
+        let delta = match self.last {
+            Some(last) => ((timestamp - last) / 1000.0) as f32,
+            None => 0.0,
+        };
+        self.last = Some(timestamp);
+        self.accumulator += delta.max(0.0).min(MAX_FRAME);
+        let mut steps = 0;
+        while self.accumulator >= STEP {
+            self.accumulator -= STEP;
+            steps += 1;
+        }
+        steps
+    }
+}
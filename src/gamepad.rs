@@ -0,0 +1,165 @@
+use yew::{html, Html};
+use stdweb::web::Gamepad;
+
+use crate::replay::*;
+use crate::Model;
+
+/// Stick deflection below this is treated as the stick resting.
+const DEADZONE: f32 = 0.2;
+/// How far a stick must be pushed to count as an arrow press in menus.
+const MENU_PUSH: f32 = 0.6;
+
+/// What the buttons and sticks do, depending on the state of the game.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PadMode {
+    /// The left stick walks; the digit picker answers obstacles.
+    Walk,
+    /// The digit picker types the answer and the d-pad moves the caret.
+    Answer,
+    /// Sticks and d-pad press the arrow keys.
+    Menu,
+}
+
+/// The characters around the radial digit picker, clockwise from the top.
+pub const PICKER: [&str; 12] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "-", "/"];
+
+/// Standard mapping button indices and the key each one presses.
+fn button_key(button: usize, mode: PadMode) -> Option<&'static str> {
+    match (button, mode) {
+        (0, _) => Some("Enter"),
+        (1, PadMode::Menu) => Some("Escape"),
+        (1, _) => Some("Backspace"),
+        (9, _) => Some("Escape"),
+        (12, _) => Some("ArrowUp"),
+        (13, _) => Some("ArrowDown"),
+        (14, _) => Some("ArrowLeft"),
+        (15, _) => Some("ArrowRight"),
+        _ => None,
+    }
+}
+
+/// Button that types the character the right stick points at.
+const PICK_BUTTON: usize = 2;
+
+/// Scales a stick so the deadzone reads as zero and a full push has length one.
+pub fn apply_deadzone(x: f32, y: f32) -> [f32; 2] {
+    let length = (x * x + y * y).sqrt();
+    if length < DEADZONE {
+        return [0.0, 0.0];
+    }
+    let scaled = ((length - DEADZONE) / (1.0 - DEADZONE)).min(1.0);
+    [x / length * scaled, y / length * scaled]
+}
+
+/// Turns the first connected gamepad into the same input events the keyboard produces, so that
+/// recordings and playback work unchanged. Polled once per animation frame.
+pub struct Gamepads {
+    pub connected: bool,
+    /// The picker entry the right stick points at.
+    pub picked: Option<usize>,
+    pressed: Vec<bool>,
+    stick: [f32; 2],
+    menu_arrow: Option<&'static str>,
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        Gamepads {
+            connected: false,
+            picked: None,
+            pressed: Vec::new(),
+            stick: [0.0, 0.0],
+            menu_arrow: None,
+        }
+    }
+    pub fn poll(&mut self, mode: PadMode) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+        let pad = Gamepad::get_all().into_iter().filter_map(|p| p).find(|p| p.connected());
+        let pad = match pad {
+            Some(pad) => pad,
+            None => {
+                self.connected = false;
+                return events;
+            },
+        };
+        self.connected = true;
+        let axes: Vec<f32> = pad.axes().iter().map(|&a| a as f32).collect();
+        let axis = |i: usize| axes.get(i).cloned().unwrap_or(0.0);
+        // Screen y grows downward; movement y grows upward.
+        let left = apply_deadzone(axis(0), -axis(1));
+        let right = apply_deadzone(axis(2), -axis(3));
+
+        self.picked = if right == [0.0, 0.0] {
+            None
+        } else {
+            let angle = right[0].atan2(right[1]).to_degrees();
+            let sector = 360.0 / PICKER.len() as f32;
+            Some((((angle + sector / 2.0 + 360.0) % 360.0) / sector) as usize % PICKER.len())
+        };
+
+        let pressed: Vec<bool> = pad.buttons().iter().map(|b| b.pressed()).collect();
+        for (i, &down) in pressed.iter().enumerate() {
+            let was = self.pressed.get(i).cloned().unwrap_or(false);
+            if down == was {
+                continue;
+            }
+            if i == PICK_BUTTON && mode != PadMode::Menu {
+                if let (true, Some(picked)) = (down, self.picked) {
+                    events.push(InputEvent::KeyDown(PICKER[picked].into()));
+                    events.push(InputEvent::KeyUp(PICKER[picked].into()));
+                }
+            } else if let Some(key) = button_key(i, mode) {
+                events.push(if down { InputEvent::KeyDown(key.into()) } else { InputEvent::KeyUp(key.into()) });
+            }
+        }
+        self.pressed = pressed;
+
+        let stick = if mode == PadMode::Walk { left } else { [0.0, 0.0] };
+        let moved = (stick[0] - self.stick[0]).abs() > 0.01 || (stick[1] - self.stick[1]).abs() > 0.01;
+        let released = stick == [0.0, 0.0] && self.stick != stick;
+        if moved || released {
+            events.push(InputEvent::Stick(stick[0], stick[1]));
+            self.stick = stick;
+        }
+
+        let arrow = if mode == PadMode::Menu {
+            if left[1] > MENU_PUSH { Some("ArrowUp") }
+            else if left[1] < -MENU_PUSH { Some("ArrowDown") }
+            else if left[0] < -MENU_PUSH { Some("ArrowLeft") }
+            else if left[0] > MENU_PUSH { Some("ArrowRight") }
+            else { None }
+        } else {
+            None
+        };
+        if arrow != self.menu_arrow {
+            if let Some(key) = self.menu_arrow {
+                events.push(InputEvent::KeyUp(key.into()));
+            }
+            if let Some(key) = arrow {
+                events.push(InputEvent::KeyDown(key.into()));
+            }
+            self.menu_arrow = arrow;
+        }
+        events
+    }
+    /// The ring of characters the right stick picks from, centred on the origin.
+    pub fn render_picker(&self) -> Html<Model> {
+        let sector = 2.0 * std::f32::consts::PI / PICKER.len() as f32;
+        html! {
+            <g class="picker",>
+                <circle class="ring", cx=0, cy=0, r=80,></circle>
+                {for PICKER.iter().enumerate().map(|(i, label)| {
+                    let angle = i as f32 * sector;
+                    let (x, y) = (angle.sin() * 60.0, -angle.cos() * 60.0);
+                    html! {
+                        <g class={if self.picked == Some(i) { "pick selected" } else { "pick" }},>
+                            <circle cx=x, cy=y, r=16,></circle>
+                            <text x=x, y={y + 7.0},>{ label }</text>
+                        </g>
+                    }
+                })}
+                <text class="help", x=0, y=7,>{ "X" }</text>
+            </g>
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use yew::{html, Html};
+use rand::prelude::*;
+
+use crate::Model;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Item {
+    Potion,
+    SkipScroll,
+    HintToken,
+    Shield,
+}
+
+impl Item {
+    pub const ALL: [Item; 4] = [Item::Potion, Item::SkipScroll, Item::HintToken, Item::Shield];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Item::Potion => "Potion",
+            Item::SkipScroll => "Skip Scroll",
+            Item::HintToken => "Hint Token",
+            Item::Shield => "Shield",
+        }
+    }
+    fn index(&self) -> usize {
+        Item::ALL.iter().position(|item| item == self).unwrap()
+    }
+    fn icon(&self) -> Html<Model> {
+        match self {
+            Item::Potion => html! {
+                <path d="M -5 -15 L 5 -15 L 5 -8 L 12 5 A 12 12 0 1 1 -12 5 L -5 -8 Z",></path>
+            },
+            Item::SkipScroll => html! {
+                <g>
+                    <rect x=-12, y=-14, width=24, height=28, rx=4, ry=4,></rect>
+                    <path d="M -6 -6 L 2 0 L -6 6 M 2 -6 L 10 0 L 2 6",></path>
+                </g>
+            },
+            Item::HintToken => html! {
+                <g>
+                    <circle cx=0, cy=0, r=14,></circle>
+                    <text class="glyph", x=0, y=7,>{"?"}</text>
+                </g>
+            },
+            Item::Shield => html! {
+                <path d="M 0 -15 L 13 -10 L 11 5 L 0 15 L -11 5 L -13 -10 Z",></path>
+            },
+        }
+    }
+}
+
+/// Items carried by the player between battles.
+pub struct Inventory {
+    counts: [usize; 4],
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Inventory {
+            counts: [1, 0, 1, 0],
+        }
+    }
+    pub fn add(&mut self, item: Item) {
+        self.counts[item.index()] += 1;
+    }
+    pub fn count(&self, item: Item) -> usize {
+        self.counts[item.index()]
+    }
+    pub fn take(&mut self, item: Item) -> bool {
+        let count = &mut self.counts[item.index()];
+        if *count > 0 {
+            *count -= 1;
+            true
+        } else {
+            false
+        }
+    }
+    pub fn render(&self) -> Html<Model> {
+        html! {
+            <g class="inventory",>
+            {for Item::ALL.iter().enumerate().map(|(i, item)| {
+                let count = self.count(*item);
+                html! {
+                    <g class={if count > 0 { "item" } else { "item empty" }}, transform={format!("translate({}, 0)", i * 50)},>
+                        <rect class="slot", x=-20, y=-20, width=40, height=40, rx=5, ry=5,></rect>
+                        { item.icon() }
+                        <text class="count", x=18, y=18,>{ count }</text>
+                        <text class="key", x=-18, y=-8,>{ format!("F{}", i + 1) }</text>
+                    </g>
+                }
+            })}
+            </g>
+        }
+    }
+}
+
+/// What a defeated enemy leaves behind: more often something the higher its level.
+pub fn roll_drop<R: Rng + ?Sized>(rng: &mut R, level: usize) -> Option<Item> {
+    let chance = (0.3 + 0.1 * level as f64).min(0.9);
+    if rng.gen_bool(chance) {
+        Item::ALL.choose(rng).cloned()
+    } else {
+        None
+    }
+}
@@ -0,0 +1,137 @@
+/// The width of the stage that menus and other fixed screens are drawn on, scaled to fit the
+/// viewport.
+pub const STAGE_WIDTH: f32 = 1000.0;
+pub const STAGE_HEIGHT: f32 = 600.0;
+
+/// Where things go on screen for the current window shape. The viewport is always 600 units on
+/// its short side: landscape windows widen it up to 1400, and portrait windows get a tall
+/// arrangement with the enemy above the problem card instead of beside it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    pub width: f32,
+    pub height: f32,
+    pub portrait: bool,
+    pub enemy: [f32; 2],
+    pub fighter: [f32; 2],
+    pub stats: [f32; 2],
+    /// The problem card as x, y, width and height.
+    pub card: [f32; 4],
+    pub problem: [f32; 2],
+    pub hint: [f32; 2],
+    pub answer: [f32; 2],
+    pub inventory: [f32; 2],
+    pub gold: [f32; 2],
+    pub combo: [f32; 2],
+    pub victory: [f32; 2],
+    /// How long each health bar is, measured in from its edge of the screen.
+    pub bar_width: f32,
+    pub numpad: [f32; 2],
+    pub picker: [f32; 2],
+    /// Where the touch pads and gamepad picker go on the overland and other screens.
+    pub walk_numpad: [f32; 2],
+    pub walk_picker: [f32; 2],
+    pub dpad: [f32; 2],
+    pub menu_buttons: [f32; 2],
+    pub touch_toggle: [f32; 2],
+}
+
+impl Layout {
+    /// The layout for a window `width` by `height` pixels.
+    pub fn fit(width: f32, height: f32) -> Self {
+        if width > 0.0 && height > width * 1.1 {
+            Layout::portrait()
+        } else {
+            let aspect = if height > 0.0 { width / height } else { STAGE_WIDTH / STAGE_HEIGHT };
+            Layout::landscape((STAGE_HEIGHT * aspect).max(STAGE_WIDTH).min(1400.0))
+        }
+    }
+    pub fn landscape(width: f32) -> Self {
+        let center = width / 2.0;
+        let enemy = [width - 125.0, 400.0];
+        Layout {
+            width: width,
+            height: STAGE_HEIGHT,
+            portrait: false,
+            enemy: enemy,
+            fighter: [180.0, 420.0],
+            stats: [enemy[0] - 125.0, enemy[1] + 30.0],
+            card: [center - 250.0, 20.0, 500.0, 500.0],
+            problem: [center, 300.0],
+            hint: [center, 490.0],
+            answer: [center, 560.0],
+            inventory: [40.0, 560.0],
+            gold: [20.0, 525.0],
+            combo: [95.0, 70.0],
+            victory: [center, 200.0],
+            bar_width: center - 25.0,
+            numpad: [20.0, 110.0],
+            picker: [130.0, 260.0],
+            walk_numpad: [width - 185.0, 110.0],
+            walk_picker: [width - 100.0, 300.0],
+            dpad: [110.0, 430.0],
+            menu_buttons: [width - 130.0, 400.0],
+            touch_toggle: [width - 50.0, 550.0],
+        }
+    }
+    pub fn portrait() -> Self {
+        let width = 600.0;
+        let enemy = [440.0, 310.0];
+        Layout {
+            width: width,
+            height: 1150.0,
+            portrait: true,
+            enemy: enemy,
+            fighter: [140.0, 330.0],
+            stats: [enemy[0] - 125.0, enemy[1] + 30.0],
+            card: [50.0, 420.0, 500.0, 400.0],
+            problem: [300.0, 640.0],
+            hint: [300.0, 790.0],
+            answer: [300.0, 870.0],
+            inventory: [40.0, 940.0],
+            gold: [20.0, 915.0],
+            combo: [95.0, 70.0],
+            victory: [300.0, 250.0],
+            bar_width: width / 2.0 - 10.0,
+            numpad: [212.0, 910.0],
+            picker: [300.0, 1030.0],
+            walk_numpad: [width - 185.0, 110.0],
+            walk_picker: [width - 100.0, 575.0],
+            dpad: [110.0, 980.0],
+            menu_buttons: [width - 130.0, 950.0],
+            touch_toggle: [width - 50.0, 1100.0],
+        }
+    }
+    pub fn view_box(&self) -> String {
+        format!("0 0 {} {}", self.width, self.height)
+    }
+    pub fn center(&self) -> [f32; 2] {
+        [self.width / 2.0, self.height / 2.0]
+    }
+    /// Fits the fixed size stage inside the viewport, centered.
+    pub fn stage(&self) -> String {
+        let scale = (self.width / STAGE_WIDTH).min(self.height / STAGE_HEIGHT);
+        format!("translate({}, {}) scale({})",
+            (self.width - STAGE_WIDTH * scale) / 2.0, (self.height - STAGE_HEIGHT * scale) / 2.0, scale)
+    }
+    /// Where blows land on the enemy.
+    pub fn enemy_hit(&self) -> [f32; 2] {
+        [self.enemy[0] - 55.0, self.enemy[1] - 150.0]
+    }
+    pub fn enemy_body(&self) -> [f32; 2] {
+        [self.enemy[0], self.enemy[1] - 100.0]
+    }
+    /// Where damage dealt to the enemy is shown.
+    pub fn enemy_label(&self) -> [f32; 2] {
+        [self.enemy[0], self.enemy[1] - 220.0]
+    }
+    pub fn fighter_hit(&self) -> [f32; 2] {
+        [self.fighter[0], self.fighter[1] - 170.0]
+    }
+    pub fn fighter_label(&self) -> [f32; 2] {
+        [self.fighter[0], self.fighter[1] - 190.0]
+    }
+    /// Where results such as "Correct!" are shown, near the top of the problem card.
+    pub fn message(&self) -> [f32; 2] {
+        [self.card[0] + self.card[2] / 2.0, self.card[1] + 90.0]
+    }
+}
@@ -1,17 +1,30 @@
 #![recursion_limit="256"]
 
-use yew::{html, Component, ComponentLink, Html, Renderable, ShouldRender};
-use yew::services::{IntervalService, ConsoleService, Task};
-use yew::events::{KeyDownEvent, KeyUpEvent, IKeyboardEvent};
+use std::cell::RefCell;
+use std::rc::Rc;
 
-use std::time::Duration;
+use yew::{html, Component, ComponentLink, Html, Renderable, ShouldRender};
+use yew::services::{RenderService, ConsoleService, Task};
+use yew::events::{KeyDownEvent, KeyUpEvent, TouchStart, ResizeEvent, IEvent, IKeyboardEvent};
+use yew::services::storage::{StorageService, Area};
+use yew::format::Text;
 
 use stdweb::web::{window, IEventTarget};
 
-use rand::distributions::{Normal, Distribution};
 use rand::prelude::*;
 
-use cgmath::Vector2;
+use cgmath::{Vector2, InnerSpace};
+
+const PLAYER_RADIUS: f32 = 10.0;
+const OBSTACLE_HALF: f32 = 50.0;
+const OBSTACLE_REACH: f32 = 150.0;
+/// How far the player walks between footstep sounds.
+const STRIDE: f32 = 40.0;
+
+/// How long the victory pose holds before the battle hands back to the overland.
+const VICTORY_TIME: f32 = 2.0;
+
+const OVERLAND_MAP: &str = include_str!("../static/overland.json");
 
 mod problem;
 use problem::*;
@@ -22,81 +35,133 @@ use textbox::*;
 mod enemy;
 use enemy::*;
 
+mod bestiary;
+use bestiary::*;
+
+mod boss;
+
+mod combo;
+use combo::*;
+
+mod items;
+use items::*;
+
+mod player;
+use player::*;
+
+mod shop;
+use shop::*;
+
+mod avatar;
+use avatar::*;
+
+mod anim;
+use anim::*;
+
 mod context;
 use context::*;
 
 mod direction;
 use direction::*;
 
-pub struct Particle {
-    vel: [f32; 2],
-    pos: [f32; 2],
-    grav: [f32; 2],
-    damp: f32,
-    life: f32,
-}
+mod replay;
+use replay::*;
+
+mod frame;
+use frame::*;
+
+mod terrain;
+use terrain::*;
+
+mod tilemap;
+use tilemap::*;
+
+mod encounter;
+use encounter::*;
+
+mod transition;
+use transition::*;
+
+mod particles;
+use particles::*;
+
+mod floattext;
+use floattext::*;
+
+mod touchpad;
+use touchpad::*;
+
+mod gamepad;
+use gamepad::*;
+
+mod bindings;
+use bindings::*;
+
+mod settings;
+use settings::*;
+
+mod pause;
+use pause::*;
+
+mod audio;
+use audio::*;
+
+mod words;
+
+mod speech;
+use speech::*;
+
+mod layout;
+use layout::*;
 
-impl Particle {
-    fn tick(&mut self, delta: f32) {
-        for i in 0..2 {
-            self.vel[i] += self.grav[i] * delta;
-            self.vel[i] *= 1.0/(self.damp*delta).exp2();
-            self.pos[i] += self.vel[i] * delta;
-        }
-        self.life -= delta;
-    }
-}
-fn blast(pos: [f32; 2], vel: [f32; 2], spread: f32, count: usize, life: f32) -> impl Iterator<Item=Particle> {
-    let mut rng = SmallRng::from_entropy();
-    let xdst = Normal::new(vel[0] as f64, spread as f64);
-    let ydst = Normal::new(vel[1] as f64, spread as f64);
-    (0..count).map(move |_| {
-        Particle {
-            pos: pos,
-            vel: [xdst.sample(&mut rng) as f32, ydst.sample(&mut rng) as f32],
-            life: life,
-            damp: 0.0,
-            grav: [0.0, 400.0]
-        }
-    })
-}
-fn firework(pos: [f32; 2], vel: [f32; 2], spread: f32, count: usize, life: f32) -> impl Iterator<Item=Particle> {
-    let mut rng = SmallRng::from_entropy();
-    let xdst = Normal::new(vel[0] as f64, spread as f64);
-    let ydst = Normal::new(vel[1] as f64, spread as f64);
-    (0..count).map(move |_| {
-        Particle {
-            pos: pos,
-            vel: [xdst.sample(&mut rng) as f32, ydst.sample(&mut rng) as f32],
-            life: life,
-            damp: 10.0,
-            grav: [0.0, 0.0]
-        }
-    })
-}
 pub struct Overland {
     pub land_pos: Vector2<f32>,
-    pub particles: Vec<Particle>,
+    pub particles: ParticleSystem,
+    pub ambient: Vec<(Emitter, [f32; 2])>,
     pub obstacles: Vec<([f32; 2], Box<Problem>)>,
-    pub encounters: Vec<Box<Enemy>>,
+    pub encounters: Encounters,
+    pub map: TileMap,
+    pub terrain: Terrain,
+    pub player: Player,
+    pub shops: Vec<ShopSite>,
+    pub facing: Facing,
+    pub walking: bool,
+    /// Distance walked since the last footstep sound.
+    pub stride: f32,
 }
 
 impl Overland {
-    fn new() -> Self {
+    fn new(rng: &mut SmallRng) -> Self {
+        let map = TileMap::from_tiled(OVERLAND_MAP).expect("built in overland map is invalid");
+        let terrain = map.terrain();
+        let encounters = Encounters::from_map(&map, &terrain, rng).expect("built in overland map has bad encounters");
         Overland {
             land_pos: [0.0, 0.0].into(),
-            particles: vec![],
+            particles: ParticleSystem::new(),
+            ambient: vec![(pollen(), [0.0, 0.0]), (leaves(), [0.0, -350.0])],
             obstacles: vec![
                 ([650.0, 50.0], Box::new(TextProblem::new("536+329","865"))),
-                ([250.0, -80.0], Box::new(TextProblem::new("3+3","6"))),
-            ],
-            encounters: vec![
-                Box::new(Orb::new(1)),
-                Box::new(Orb::new(3)),
-                Box::new(Orb::new(5)),
+                ([250.0, -50.0], Box::new(TextProblem::new("3+3","6"))),
             ],
+            encounters: encounters,
+            terrain: terrain,
+            shops: ShopSite::from_map(&map),
+            map: map,
+            player: Player::new(),
+            facing: Facing::Down,
+            walking: false,
+            stride: 0.0,
         }
     }
+    /// Whether the player could stand at `pos`. Unsolved obstacles block the way like walls.
+    fn blocked(&self, pos: Vector2<f32>) -> bool {
+        self.terrain.blocked(pos, PLAYER_RADIUS) || self.obstacles.iter().any(|(center, _)| {
+            circle_hits_square((*center).into(), OBSTACLE_HALF, pos, PLAYER_RADIUS)
+        })
+    }
+    fn walk(&mut self, step: Vector2<f32>) {
+        self.land_pos = slide(self.land_pos, step, |pos| self.blocked(pos));
+    }
 }
 
 pub struct Battle {
@@ -105,13 +170,79 @@ pub struct Battle {
     pub state: FighterState,
     pub enemy_props: EnemyProps,
     pub problem: Option<Box<Problem>>,
-    pub particles: Vec<Particle>,
+    pub particles: ParticleSystem,
+    pub texts: FloatingTexts,
+    pub combo: Combo,
+    pub hint: Option<String>,
+    pub shield: bool,
+    pub won: bool,
+    /// Whether the current problem is still waiting to be read aloud.
+    pub unread: bool,
+}
+
+impl Battle {
+    fn new(land: Overland, mut enemy: Box<Enemy>, rng: &mut SmallRng) -> Self {
+        Battle {
+            land: land,
+            enemy_props: enemy.get_properties(),
+            problem: Some(enemy.generate_problem(rng)),
+            enemy: enemy,
+            state: FighterState { health: 1.0 },
+            particles: ParticleSystem::new(),
+            texts: FloatingTexts::new(),
+            combo: Combo::new(),
+            hint: None,
+            shield: false,
+            won: false,
+            unread: true,
+        }
+    }
+    fn next_problem(&mut self, rng: &mut SmallRng) {
+        self.problem = Some(self.enemy.generate_problem(rng));
+        self.unread = true;
+    }
+    /// Takes `amount` off the player's health unless a shield soaks it up, saying which at `label`.
+    fn hurt(&mut self, amount: f64, label: [f32; 2]) {
+        if self.shield {
+            self.shield = false;
+            self.texts.add("Blocked!", label, "blocked");
+        } else {
+            self.state.health -= amount;
+            self.texts.add(format!("-{:.0}%", amount * 100.0), label, "hurt");
+        }
+    }
+    /// Spends one of `item` from the inventory if the player has one and it would do anything.
+    fn use_item(&mut self, item: Item, rng: &mut SmallRng) -> bool {
+        let useful = match item {
+            Item::Potion => self.state.health < 1.0,
+            Item::SkipScroll => self.problem.is_some(),
+            Item::HintToken => self.problem.is_some() && self.hint.is_none(),
+            Item::Shield => !self.shield,
+        };
+        if !useful || !self.land.player.inventory.take(item) {
+            return false;
+        }
+        match item {
+            Item::Potion => self.state.health = (self.state.health + 0.4).min(1.0),
+            Item::SkipScroll => {
+                self.next_problem(rng);
+                self.hint = None;
+                self.combo.timer = 0.0;
+            },
+            Item::HintToken => self.hint = self.problem.as_ref().map(|p| p.hint()),
+            Item::Shield => self.shield = true,
+        }
+        true
+    }
 }
 
 pub enum State {
     Title,
     Overland(Overland),
     Battle(Battle),
+    Shop(Shop),
+    Creator(Creator),
+    Bindings(BindingsScreen),
     Empty,
 }
 
@@ -130,6 +261,13 @@ impl State {
             None
         }
     }
+    pub fn as_battle_mut(&mut self) -> Option<&mut Battle> {
+        if let State::Battle(battle) = self {
+            Some(battle)
+        } else {
+            None
+        }
+    }
     pub fn as_battle(&self) -> Option<&Battle> {
         if let State::Battle(battle) = self {
             Some(battle)
@@ -137,129 +275,448 @@ impl State {
             None
         }
     }
+    pub fn name(&self) -> &'static str {
+        match self {
+            State::Title => "title",
+            State::Overland(_) => "overland",
+            State::Battle(_) => "battle",
+            State::Shop(_) => "shop",
+            State::Creator(_) => "creator",
+            State::Bindings(_) => "bindings",
+            State::Empty => "empty",
+        }
+    }
 }
 
 pub struct Model {
     pub state: State,
-    pub interval: IntervalService,
+    pub link: ComponentLink<Model>,
+    pub render: RenderService,
+    pub clock: FrameClock,
     pub console: ConsoleService,
     pub dir: Direction,
     pub text: TextBox,
+    pub layout: Layout,
     pub ctx: Context,
-    pub _anim_task: Box<Task>,
+    pub transition: Option<Transition>,
+    /// Whether the on-screen pads are shown. Turned on by the first touch.
+    pub touch: bool,
+    pub gamepad: Gamepads,
+    pub rng: SmallRng,
+    pub storage: StorageService,
+    pub settings: Settings,
+    /// The keys bound to items, shared with the keydown listener so it can keep them from
+    /// triggering browser shortcuts.
+    pub item_keys: Rc<RefCell<Vec<String>>>,
+    pub audio: Audio,
+    pub speech: Speech,
+    /// The latest result, for screen readers to announce.
+    pub announcement: String,
+    /// The pause menu while the game is paused. Ticks don't reach the state until it closes.
+    pub paused: Option<PauseMenu>,
+    pub recording: Option<Recording>,
+    pub playback: Option<Playback>,
+    pub _frame_task: Option<Box<Task>>,
 }
 
 pub enum Msg {
-    AnimTick(f32),
+    Frame(f64),
     KeyDown(KeyDownEvent),
     KeyUp(KeyUpEvent),
-}
-
-fn eval_particles(particles: &mut Vec<Particle>, delta: f32) {
-    for particle in particles.iter_mut() {
-        particle.tick(delta);
-    }
-    for i in (0..particles.len()).rev() {
-        if particles[i].life < 0.0 {
-            particles.swap_remove(i);
-        }
-    }
+    /// A key pressed or released on the on-screen pads.
+    Press(String),
+    Release(String),
+    Touched,
+    ToggleTouch,
+    Resize,
 }
 
 impl Component for Model {
     type Message = Msg;
     type Properties = ();
     fn create(_: Self::Properties, mut link: ComponentLink<Self>) -> Self {
-        let mut interval = IntervalService::new();
-        let handle = interval.spawn(Duration::from_millis(50), link.send_back(|_| Msg::AnimTick(0.05)));
         let mut console = ConsoleService::new();
         console.log("Starting up");
+        let mut storage = StorageService::new(Area::Local);
+        let settings = Settings::load(&mut storage);
+        let item_keys = Rc::new(RefCell::new(settings.bindings.item_keys()));
         let callback = link.send_back(|e:KeyDownEvent| Msg::KeyDown(e));
-        window().add_event_listener(move |e: KeyDownEvent| callback.emit(e));
+        let blocked = item_keys.clone();
+        window().add_event_listener(move |e: KeyDownEvent| {
+            // The item keys would otherwise open the browser's help or search.
+            if blocked.borrow().contains(&e.key()) {
+                e.prevent_default();
+            }
+            callback.emit(e)
+        });
         let callback = link.send_back(|e:KeyUpEvent| Msg::KeyUp(e));
         window().add_event_listener(move |e: KeyUpEvent| callback.emit(e));
-        Model {
+        let callback = link.send_back(|_: TouchStart| Msg::Touched);
+        window().add_event_listener(move |e: TouchStart| callback.emit(e));
+        let callback = link.send_back(|_: ResizeEvent| Msg::Resize);
+        window().add_event_listener(move |e: ResizeEvent| callback.emit(e));
+        let mut model = Model {
             state: State::Title,
-            interval: interval,
+            link: link,
+            render: RenderService::new(),
+            clock: FrameClock::new(),
             console: console,
             dir: Direction::new(),
             text: TextBox::new(),
-            ctx: Context {
-                anim_t: 0.0,
-                time_damage: 0.0,
-            },
-            _anim_task: Box::new(handle),
-        }
+            layout: Layout::fit(window().inner_width() as f32, window().inner_height() as f32),
+            ctx: Context::new(),
+            transition: None,
+            touch: false,
+            gamepad: Gamepads::new(),
+            rng: SmallRng::from_entropy(),
+            storage: storage,
+            settings: settings,
+            item_keys: item_keys,
+            audio: Audio::new(),
+            speech: Speech::new(),
+            announcement: String::new(),
+            paused: None,
+            recording: None,
+            playback: None,
+            _frame_task: None,
+        };
+        model.ctx.animations = model.settings.animations;
+        let (master, effects, music) = model.settings.volumes();
+        model.audio.set_volumes(master, effects, music);
+        model.request_frame();
+        model
     }
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
-        self.text.movement = false;
-        let mut rng = SmallRng::from_entropy();
         match msg {
-            Msg::AnimTick(x) => {
+            Msg::Frame(time) => {
+                self.request_frame();
+                let mode = match self.state {
+                    _ if self.paused.is_some() => PadMode::Menu,
+                    State::Overland(_) => PadMode::Walk,
+                    State::Battle(_) => PadMode::Answer,
+                    _ => PadMode::Menu,
+                };
+                let mut render = false;
+                for event in self.gamepad.poll(mode) {
+                    render |= self.dispatch(event);
+                }
+                let steps = self.clock.frame(time);
+                for _ in 0..steps {
+                    render |= self.dispatch(InputEvent::Tick(STEP));
+                }
+                render
+            },
+            Msg::KeyDown(e) => self.dispatch(InputEvent::KeyDown(e.key())),
+            Msg::KeyUp(e) => self.dispatch(InputEvent::KeyUp(e.key())),
+            Msg::Press(key) => self.dispatch(InputEvent::KeyDown(key)),
+            Msg::Release(key) => self.dispatch(InputEvent::KeyUp(key)),
+            Msg::Touched => {
+                let changed = !self.touch;
+                self.touch = true;
+                changed
+            },
+            Msg::ToggleTouch => {
+                self.touch = !self.touch;
+                true
+            },
+            Msg::Resize => {
+                self.layout = Layout::fit(window().inner_width() as f32, window().inner_height() as f32);
+                true
+            },
+        }
+    }
+}
+
+
+impl Renderable<Model> for Model {
+    fn view(&self) -> Html<Self> {
+        html! {
+            <div>
+                <svg viewBox={self.layout.view_box()}, xmlns="http://www.w3.org/2000/svg", role="application", aria-label="Mathland",
+                    class={format!("{} {}", self.settings.text_size.class(), self.settings.theme.class())},>
+                {
+                    match self.transition {
+                        Some(ref transition) => transition.render(self.scene(), [self.layout.width, self.layout.height]),
+                        None => self.scene(),
+                    }
+                }
+                {
+                    match self.paused {
+                        Some(ref menu) if self.state.name() != "bindings" => html! {
+                            <g>
+                                <rect class="pause_shade", x=0, y=0, width={self.layout.width}, height={self.layout.height},></rect>
+                                <g transform={self.layout.stage()},>{ menu.render(&self.settings) }</g>
+                            </g>
+                        },
+                        _ => html! { <g></g> },
+                    }
+                }
+                { self.touch_controls() }
+                { self.gamepad_picker() }
+                </svg>
+                <div class="screen_reader_only", role="status", aria-live="polite",>{ &self.announcement }</div>
+            </div>
+        }
+    }
+}
+
+impl Model {
+    /// Battle and the overland are laid out for the window; other screens are drawn on the fixed
+    /// stage.
+    fn scene(&self) -> Html<Self> {
+        let screen = match self.state {
+            State::Battle(_) => return self.battle(),
+            State::Overland(_) => return self.overland(),
+            State::Title => html! {
+                <circle cx=10, cy=0, r=100,></circle>
+            },
+            State::Shop(ref shop) => shop.render(&self.settings.bindings),
+            State::Creator(ref creator) => creator.render(&self.ctx),
+            State::Bindings(ref screen) => screen.render(&self.settings.bindings),
+            _ => html! {
+                <circle cx=500, cy=0, r=100,></circle>
+            },
+        };
+        html! {
+            <g transform={self.layout.stage()},>{ screen }</g>
+        }
+    }
+    /// The on-screen pads for whatever the current state needs, plus the button that shows them.
+    fn touch_controls(&self) -> Html<Self> {
+        let pads = if !self.touch {
+            html! { <g></g> }
+        } else {
+            let at = |pos: [f32; 2]| format!("translate({}, {})", pos[0], pos[1]);
+            match self.state {
+                State::Title => html! {
+                    <g transform={self.layout.stage()},>{ button("Start", "Enter", 450.0, 400.0, 100.0, 56.0) }</g>
+                },
+                State::Overland(_) => html! {
+                    <g>
+                        <g transform={at(self.layout.dpad)},>{ dpad() }</g>
+                        <g transform={at(self.layout.walk_numpad)},>{ numpad() }</g>
+                    </g>
+                },
+                State::Battle(_) => html! {
+                    <g transform={at(self.layout.numpad)},>{ numpad() }</g>
+                },
+                _ => html! {
+                    <g>
+                        <g transform={at(self.layout.dpad)},>{ dpad() }</g>
+                        <g transform={at(self.layout.menu_buttons)},>{ menu_buttons() }</g>
+                    </g>
+                },
+            }
+        };
+        html! {
+            <g class="touch_controls",>
+                { pads }
+                <g class="touch_toggle", onclick=|_| Msg::ToggleTouch,
+                    transform={format!("translate({}, {})", self.layout.touch_toggle[0], self.layout.touch_toggle[1])},>
+                    <rect x=0, y=0, width=40, height=40, rx=8, ry=8,></rect>
+                    <text x=20, y=28,>{ "⌨" }</text>
+                </g>
+            </g>
+        }
+    }
+    /// The radial digit picker, while a gamepad is connected and there is something to answer.
+    fn gamepad_picker(&self) -> Html<Self> {
+        let at = match self.state {
+            State::Battle(_) => self.layout.picker,
+            State::Overland(_) => self.layout.walk_picker,
+            _ => return html! { <g></g> },
+        };
+        if !self.gamepad.connected {
+            return html! { <g></g> };
+        }
+        html! {
+            <g transform={format!("translate({}, {})", at[0], at[1])},>{ self.gamepad.render_picker() }</g>
+        }
+    }
+    fn request_frame(&mut self) {
+        let callback = self.link.send_back(Msg::Frame);
+        self._frame_task = Some(Box::new(self.render.request_animation_frame(callback)));
+    }
+    fn dispatch(&mut self, event: InputEvent) -> ShouldRender {
+        match event {
+            InputEvent::KeyDown(ref key) if key == "F9" => {
+                self.toggle_recording();
+                true
+            },
+            InputEvent::KeyDown(ref key) if key == "F10" => {
+                self.toggle_playback();
+                true
+            },
+            InputEvent::Tick(_) if self.playback.is_some() => {
+                let due = self.playback.as_mut().map(|p| p.advance()).unwrap_or_default();
+                for event in due {
+                    self.input(event);
+                }
+                if self.playback.as_ref().map(|p| p.finished()).unwrap_or(false) {
+                    self.console.log("Playback finished");
+                    self.playback = None;
+                }
+                true
+            },
+            _ if self.playback.is_some() => false,
+            event => {
+                if let Some(ref mut recording) = self.recording {
+                    recording.push(event.clone());
+                }
+                self.input(event)
+            },
+        }
+    }
+    fn input(&mut self, event: InputEvent) -> ShouldRender {
+        self.text.movement = self.state.as_battle().is_some();
+        let from = self.state.name();
+        if let InputEvent::KeyDown(_) = event {
+            self.audio.resume();
+        }
+        match event {
+            InputEvent::KeyDown(ref x) if self.paused.is_some() && self.state.name() != "bindings" => {
+                self.pause_key(x);
+                true
+            },
+            InputEvent::KeyDown(_) if self.transition.is_some() => false,
+            InputEvent::Tick(_) if self.paused.is_some() => false,
+            InputEvent::Tick(x) => {
+                if self.transition.as_ref().map(|t| t.finished()).unwrap_or(false) {
+                    self.transition = None;
+                }
+                if let Some(ref mut transition) = self.transition {
+                    transition.tick(x);
+                }
                 let new_state = match self.swap_state_out() {
                     State::Title => State::Title {
                     },
                     State::Overland(mut land) => {
-                        eval_particles(&mut land.particles, x);
+                        land.particles.tick(x);
+                        let camera = [land.land_pos.x, -land.land_pos.y];
+                        for (emitter, offset) in land.ambient.iter_mut() {
+                            emitter.pos = [camera[0] + offset[0], camera[1] + offset[1]];
+                            land.particles.stream(emitter, x);
+                        }
                         let speed = 100.0 * x;
                         let d = self.dir.direction();
-                        land.land_pos[0] += d[0] * speed;
-                        land.land_pos[1] += d[1] * speed;
-                        if d != [0.0, 0.0] {
-                            if 0.01 > rng.gen_range(0.0, 1.0) {
-                                let items: Vec<usize> = (0..land.encounters.len()).collect();
-                                if let Some(mut e) = items.choose(&mut rng).map(|&i| land.encounters.swap_remove(i)) {
-                                    self.ctx.anim_t = 0.0;
-                                    let enemy_props = e.get_properties();
-                                    let b = Battle {
-                                        land: land,
-                                        enemy_props: enemy_props,
-                                        problem: Some(e.generate_problem()),
-                                        enemy: e,
-                                        state: FighterState { health: 1.0 },
-                                        particles: Vec::new()
-                                    };
-                                    State::Battle(b)
-                                } else {
-                                    State::Overland(land)
-                                }
+                        let before = land.land_pos;
+                        land.walk(Vector2::new(d[0], d[1]) * speed);
+                        let walked = (land.land_pos - before).magnitude();
+                        if let Some(facing) = Facing::from_direction(d) {
+                            land.facing = facing;
+                        }
+                        land.walking = walked > 0.0;
+                        land.stride += walked;
+                        if land.stride > STRIDE {
+                            land.stride -= STRIDE;
+                            self.audio.play(Sound::Step);
+                        }
+                        let shop = if walked > 0.0 {
+                            land.shops.iter().find(|site| site.contains(land.land_pos)).map(|site| site.center)
+                        } else {
+                            None
+                        };
+                        if let Some(center) = shop {
+                            let away = before + (before - center).normalize() * 20.0;
+                            land.land_pos = if land.blocked(away) { before } else { away };
+                            self.text = TextBox::new();
+                            State::Shop(Shop::new(land))
+                        } else {
+                            let mut enemy = land.encounters.boss_at(land.land_pos);
+                            if enemy.is_none() {
+                                enemy = land.encounters.roam(land.land_pos, x, &land.terrain, &mut self.rng);
+                            }
+                            if enemy.is_none() && walked > 0.0 {
+                                enemy = land.encounters.step(land.land_pos, walked, &mut self.rng);
+                            }
+                            if let Some(e) = enemy {
+                                State::Battle(Battle::new(land, e, &mut self.rng))
                             } else {
                                 State::Overland(land)
                             }
-                        } else {
-                            State::Overland(land)
                         }
                     },
                     State::Battle(mut b) => {
-                        eval_particles(&mut b.particles, x);
-                        State::Battle(b)
+                        b.particles.tick(x);
+                        b.texts.tick(x);
+                        b.combo.tick(x);
+                        if b.won {
+                            if self.ctx.since(Event::Victory).map_or(false, |t| t > VICTORY_TIME) {
+                                State::Overland(b.land)
+                            } else {
+                                State::Battle(b)
+                            }
+                        } else {
+                            if let Some(EnemyAction::Attack(amount)) = b.enemy.act(x as f64) {
+                                b.hurt(amount * self.settings.difficulty.damage_taken(), self.layout.fighter_label());
+                                self.announce(format!("{} attacks! Your health is {:.0}%", b.enemy_props.name, b.state.health * 100.0));
+                                self.ctx.trigger(Event::Hurt);
+                                self.ctx.trigger(Event::ScreenShake);
+                                self.audio.play(Sound::Hit);
+                                b.particles.emit(blast(self.layout.fighter_hit(), [-200.0, 200.0], 500.0, 10, 4.0));
+                            }
+                            State::Battle(b)
+                        }
                     },
                     x => x
                 };
                 self.swap_state_in(new_state);
-                self.ctx.anim_t += x;
-                self.ctx.time_damage += x;
+                self.begin_transition(from);
+                self.update_music();
+                self.update_speech();
+                self.ctx.tick(x);
                 true
             },
-            Msg::KeyDown(x) => {
-                self.text.down(&x);
-                self.dir.down(&x);
-                self.console.log(&format!("{:?}", x.key()));
+            InputEvent::KeyDown(x) => {
+                let action = self.settings.bindings.action(&x);
+                // Back pauses wherever it has nothing else to do.
+                let pause = match self.state {
+                    State::Title | State::Overland(_) | State::Battle(_) => action == Some(Action::Back) || action == Some(Action::Pause),
+                    State::Bindings(_) => false,
+                    _ => action == Some(Action::Pause),
+                };
+                if pause {
+                    self.paused = Some(PauseMenu::new());
+                    return true;
+                }
+                let item = action.and_then(|a| a.item()).filter(|_| self.state.as_battle().is_some());
+                // Letters and space bound to an action, such as WASD, aren't typed into the answer.
+                // Named keys still reach the text box so the arrows can move the caret.
+                let typed = x.chars().count() == 1 && !x.chars().all(|c| c.is_ascii_digit());
+                let bound = typed && action.is_some();
+                if item.is_none() && !bound {
+                    self.text.filter = match self.state.as_battle().and_then(|b| b.problem.as_ref()) {
+                        Some(p) => p.input_filter(),
+                        None => InputFilter::Any,
+                    };
+                    self.text.down(&x);
+                }
+                self.dir.down(action);
+                self.console.log(&format!("{:?}", x));
+                let submit = action == Some(Action::Submit);
                 let newstate = match self.swap_state_out() {
                     State::Title => {
-                        if x.key() == "Enter" {
-                            State::Overland(Overland::new())
+                        if submit {
+                            State::Creator(Creator::new(Overland::new(&mut self.rng)))
+                        } else if action == Some(Action::Controls) {
+                            State::Bindings(BindingsScreen::new(State::Title))
                         } else {
                             State::Title
                         }
                     },
                     State::Overland(mut land) => {
-                        self.text.movement = false;
-                        if x.key() == "Enter" {
+                        if submit {
                             for i in (0..land.obstacles.len()).rev() {
-                                if land.obstacles[i].1.test_correct(&self.text.text) {
+                                let center: Vector2<f32> = land.obstacles[i].0.into();
+                                let in_reach = (center - land.land_pos).magnitude() < OBSTACLE_REACH;
+                                if in_reach && land.obstacles[i].1.test_correct(&self.text.text) {
                                     let (pos, _) = land.obstacles.swap_remove(i);
-                                    land.particles.extend(firework([pos[0], -pos[1]], [0.0, 0.0], 500.0, 20, 0.75));
+                                    if let Some(&item) = Item::ALL.choose(&mut self.rng) {
+                                        land.player.inventory.add(item);
+                                    }
+                                    self.audio.play(Sound::Firework);
+                                    land.particles.emit(firework([pos[0], -pos[1]], [0.0, 0.0], 500.0, 20, 0.75));
+                                    land.particles.emit(digits([pos[0], -pos[1]], 8));
                                 }
                             }
                             self.text = TextBox::new();
@@ -267,73 +724,265 @@ impl Component for Model {
                         State::Overland(land)
                     },
                     State::Battle(mut battle) => {
-                        if x.key() == "Enter" && battle.problem.is_some() {
+                        if battle.won {
+                            if submit {
+                                State::Overland(battle.land)
+                            } else {
+                                State::Battle(battle)
+                            }
+                        } else if let Some(item) = item {
+                            battle.use_item(item, &mut self.rng);
+                            State::Battle(battle)
+                        } else if submit && battle.problem.is_some() {
                             let correct = if let Some(ref p) = battle.problem {
                                 p.test_correct(&self.text.text)
                             } else {
                                 false
                             };
                             if correct {
-                                battle.enemy.damage(1.0);
-                                self.ctx.time_damage = 0.0;
-                                battle.particles.extend(blast([820.0, 250.0], [200.0, 200.0], 500.0, 10, 4.0));
+                                battle.land.player.gold += 1;
+                                let strike = battle.combo.hit();
+                                let before = battle.enemy.get_state().health;
+                                battle.enemy.damage(strike.multiplier);
+                                let dealt = before - battle.enemy.get_state().health;
+                                battle.texts.add("Correct!", self.layout.message(), "correct");
+                                self.announce(format!("Correct! {} health {:.0}%", battle.enemy_props.name, battle.enemy.get_state().health.max(0.0) * 100.0));
+                                if strike.multiplier > 1.0 {
+                                    battle.texts.add(format!("-{:.0}% x{:.1}", dealt * 100.0, strike.multiplier), self.layout.enemy_label(), "damage");
+                                } else {
+                                    battle.texts.add(format!("-{:.0}%", dealt * 100.0), self.layout.enemy_label(), "damage");
+                                }
+                                self.ctx.trigger(Event::HitFlash);
+                                self.ctx.trigger(Event::Attack);
+                                self.audio.play(Sound::Correct);
+                                let intensity = strike.multiplier as f32;
+                                battle.particles.emit(blast(self.layout.enemy_hit(), [200.0, 200.0], 500.0 * intensity.sqrt(), (10.0 * intensity) as usize, 4.0));
+                                if strike.special {
+                                    self.ctx.trigger(Event::ScreenShake);
+                                    self.audio.play(Sound::Firework);
+                                    battle.particles.emit(firework(self.layout.enemy_hit(), [0.0, 0.0], 1000.0, 40, 1.0));
+                                    battle.particles.emit(stars(self.layout.enemy_hit(), 600.0, 20));
+                                }
+                                let props = battle.enemy.get_properties();
+                                if props.name != battle.enemy_props.name {
+                                    self.audio.play(Sound::Firework);
+                                    battle.particles.emit(firework(self.layout.enemy_body(), [0.0, 0.0], 800.0, 40, 1.0));
+                                }
+                                battle.enemy_props = props;
                             } else {
-                                battle.state.health -= 0.2;
-                                battle.particles.extend(blast([180.0, 250.0], [-200.0, 200.0], 500.0, 10, 4.0));
+                                battle.combo.miss();
+                                if let Some(ref p) = battle.problem {
+                                    battle.texts.add(format!("It was {}", p.get_answer()), self.layout.message(), "answer");
+                                    self.announce(format!("Wrong, it was {}", p.get_answer()));
+                                }
+                                battle.hurt(0.2 * self.settings.difficulty.damage_taken(), self.layout.fighter_label());
+                                self.ctx.trigger(Event::Hurt);
+                                self.ctx.trigger(Event::ScreenShake);
+                                self.audio.play(Sound::Wrong);
+                                battle.particles.emit(blast(self.layout.fighter_hit(), [-200.0, 200.0], 500.0, 10, 4.0));
                                 self.console.log("INCORRECT");
                             }
                             self.text = TextBox::new();
+                            battle.hint = None;
                             if battle.enemy.get_state().health <= 0.0001 {
                                 battle.problem = None;
-                                State::Overland(battle.land)
+                                if battle.enemy.boss_phases().is_some() {
+                                    battle.land.player.gold += 50;
+                                    battle.land.player.inventory.add(Item::Potion);
+                                    battle.land.player.inventory.add(Item::Shield);
+                                } else {
+                                    let level = battle.enemy_props.level.parse().unwrap_or(1);
+                                    battle.land.player.gold += 5 * level;
+                                    if let Some(item) = roll_drop(&mut self.rng, level) {
+                                        battle.land.player.inventory.add(item);
+                                    }
+                                }
+                                battle.won = true;
+                                self.ctx.trigger(Event::Victory);
+                                self.announce(format!("Victory! {} is defeated", battle.enemy_props.name));
+                                self.audio.play(Sound::LevelUp);
+                                battle.particles.emit(firework(self.layout.enemy_body(), [0.0, 0.0], 600.0, 30, 1.0));
+                                battle.particles.emit(confetti(self.layout.width, 60.0, 2.0));
+                                State::Battle(battle)
                             } else {
-                                battle.problem = Some(battle.enemy.generate_problem());
+                                battle.next_problem(&mut self.rng);
                                 State::Battle(battle)
                             }
                         } else {
                             State::Battle(battle)
                         }
                     },
+                    State::Shop(mut shop) => {
+                        match action {
+                            Some(Action::Up) => shop.up(),
+                            Some(Action::Down) => shop.down(),
+                            Some(Action::Submit) => shop.buy(),
+                            _ => (),
+                        }
+                        self.text = TextBox::new();
+                        if action == Some(Action::Back) {
+                            State::Overland(shop.land)
+                        } else if action == Some(Action::Look) {
+                            State::Creator(Creator::new(shop.land))
+                        } else {
+                            State::Shop(shop)
+                        }
+                    },
+                    State::Creator(mut creator) => {
+                        match action {
+                            Some(Action::Up) => creator.up(),
+                            Some(Action::Down) => creator.down(),
+                            Some(Action::Left) => creator.cycle(-1),
+                            Some(Action::Right) => creator.cycle(1),
+                            _ => (),
+                        }
+                        self.text = TextBox::new();
+                        if submit || action == Some(Action::Back) {
+                            State::Overland(creator.land)
+                        } else {
+                            State::Creator(creator)
+                        }
+                    },
+                    State::Bindings(mut screen) => {
+                        self.text = TextBox::new();
+                        let done = screen.key(&x, &mut self.settings.bindings);
+                        *self.item_keys.borrow_mut() = self.settings.bindings.item_keys();
+                        if done {
+                            self.settings.save(&mut self.storage);
+                            *screen.back
+                        } else {
+                            State::Bindings(screen)
+                        }
+                    },
                     x => x,
                 };
                 self.swap_state_in(newstate);
+                self.begin_transition(from);
+                self.update_music();
+                self.update_speech();
                 true
             },
-            Msg::KeyUp(x) => {
+            InputEvent::KeyUp(x) => {
                 self.text.up(&x);
-                self.dir.up(&x);
-                self.console.log(&format!("{:?}", x));
+                self.dir.up(self.settings.bindings.action(&x));
                 true
             },
+            InputEvent::Stick(x, y) => {
+                self.dir.stick = [x, y];
+                false
+            },
         }
     }
-}
-
-
-impl Renderable<Model> for Model {
-    fn view(&self) -> Html<Self> {
-        html! {
-            <div>
-                <svg viewBox="0 0 1000 600", xmlns="http://www.w3.org/2000/svg",>
-                {
-                    match self.state {
-                        State::Title => html! {
-                            <circle cx=10, cy=0, r=100,></circle>
-                        },
-                        State::Battle(_) => self.battle(),
-                        State::Overland(_) => self.overland(),
-                        _ => html! {
-                            <circle cx=500, cy=0, r=100,></circle>
-                        },
-                    }
-                }
-                </svg>
-            </div>
+    /// Puts the game back on the title screen with a fresh rng so that a recorded session can be
+    /// reproduced from its first input.
+    fn reset(&mut self, seed: u64) {
+        self.rng = SmallRng::seed_from_u64(seed);
+        self.state = State::Title;
+        self.dir = Direction::new();
+        self.text = TextBox::new();
+        self.ctx = Context::new();
+        self.ctx.animations = self.settings.animations;
+        self.transition = None;
+        self.paused = None;
+    }
+    fn pause_key(&mut self, key: &str) {
+        let action = self.settings.bindings.action(key);
+        let before = self.settings.clone();
+        let choice = match self.paused {
+            Some(ref mut menu) => menu.key(action, &mut self.settings),
+            None => return,
+        };
+        if self.settings != before {
+            self.ctx.animations = self.settings.animations;
+            let (master, effects, music) = self.settings.volumes();
+            self.audio.set_volumes(master, effects, music);
+            if !self.settings.read_aloud {
+                self.speech.cancel();
+            }
+            self.settings.save(&mut self.storage);
+        }
+        match choice {
+            PauseChoice::Stay => (),
+            PauseChoice::Resume => self.paused = None,
+            PauseChoice::Controls => {
+                let state = self.swap_state_out();
+                self.swap_state_in(State::Bindings(BindingsScreen::new(state)));
+            },
+            PauseChoice::QuitToTitle => {
+                self.paused = None;
+                self.state = State::Title;
+                self.text = TextBox::new();
+                self.dir = Direction::new();
+            },
+        }
+        self.update_music();
+        self.update_speech();
+    }
+    /// Tells screen readers `text` through the live region, which only speaks when its content
+    /// changes, so a repeat of the last message gets a trailing no-break space.
+    fn announce(&mut self, text: String) {
+        let repeat = self.announcement == text;
+        self.announcement = if repeat { format!("{}\u{a0}", text) } else { text };
+    }
+    /// Plays the music for the current state, or the boss theme in a boss battle.
+    fn update_music(&mut self) {
+        let boss = self.state.as_battle().map_or(false, |b| b.enemy.boss_phases().is_some());
+        self.audio.set_music(Track::for_state(self.state.name(), boss));
+    }
+    /// Reads the battle's problem aloud if it hasn't been yet and the player asked for that.
+    fn update_speech(&mut self) {
+        if !self.settings.read_aloud {
+            return;
+        }
+        let volume = self.settings.volumes().0;
+        if let Some(battle) = self.state.as_battle_mut().filter(|b| b.unread) {
+            if let Some(ref problem) = battle.problem {
+                self.speech.speak(&problem.speech(), volume);
+            }
+            battle.unread = false;
+        }
+    }
+    /// Starts the transition configured in `TRANSITIONS` if the last input changed the state.
+    /// Screens opened from the pause menu switch straight over, since transitions don't tick
+    /// while paused.
+    fn begin_transition(&mut self, from: &'static str) {
+        let to = self.state.name();
+        if to != from && self.settings.animations && self.paused.is_none() {
+            let focus = if to == "battle" { self.layout.enemy_body() } else { self.layout.center() };
+            self.transition = Some(Transition::new(transition_between(from, to), focus));
+        }
+    }
+    fn toggle_recording(&mut self) {
+        if let Some(recording) = self.recording.take() {
+            let log = recording.to_log();
+            self.console.log(&log);
+            let value: Text = Ok(log);
+            self.storage.store(REPLAY_KEY, value);
+            self.console.log("Recording stopped");
+        } else {
+            let seed = self.rng.gen::<u64>();
+            self.playback = None;
+            self.reset(seed);
+            self.recording = Some(Recording::new(seed));
+            self.console.log("Recording started");
+        }
+    }
+    fn toggle_playback(&mut self) {
+        if self.playback.take().is_some() {
+            self.console.log("Playback stopped");
+            return;
+        }
+        let stored: Text = self.storage.restore(REPLAY_KEY);
+        match stored.map_err(|e| e.to_string()).and_then(|log| Recording::from_log(&log)) {
+            Ok(recording) => {
+                self.recording = None;
+                self.reset(recording.seed);
+                self.playback = Some(Playback::new(recording));
+                self.console.log("Playback started");
+            },
+            Err(e) => self.console.log(&format!("No replay to play: {}", e)),
         }
     }
-}
-
-impl Model {
     fn swap_state_out(&mut self) -> State {
         let mut s = State::Empty;
         std::mem::swap(&mut s, &mut self.state);
@@ -342,35 +991,40 @@ impl Model {
     fn swap_state_in(&mut self, s: State) {
         std::mem::replace(&mut self.state, s);
     }
-    fn particles(&self, particles: &Vec<Particle>) -> Html<Self> {
-        html! {
-                    <g class="particles",>
-                    {for particles.iter().map(|particle| {
-                            html!{
-                                <g class="particle",>
-                                <circle cx={particle.pos[0]}, cy={particle.pos[1]}, r=5,></circle>
-                                <line x1={particle.pos[0]},
-                                y1={particle.pos[1]},
-                                x2={particle.pos[0]-particle.vel[0]*0.05},
-                                y2={particle.pos[1]-particle.vel[1]*0.05},></line>
-                                </g>
-                            }
-                        })
-                    }
-                    </g>
-        }
-    }
     fn battle(&self) -> Html<Self> {
         let battle = self.state.as_battle().unwrap();
-            let enemy_state = battle.enemy.get_state(); 
+            let enemy_state = battle.enemy.get_state();
+            let recoil = self.ctx.value(Event::HitFlash);
+            let knockback = recoil * 40.0 * self.ctx.wave(0.1).abs();
+            let hurt = self.ctx.value(Event::Hurt);
+            let lunge = self.ctx.value(Event::Attack) * 80.0 - hurt * 30.0;
+            let jump = if battle.won { self.ctx.value(Event::Victory) * self.ctx.wave(0.8).abs() * 10.0 } else { 0.0 };
+            let shake = self.ctx.value(Event::ScreenShake) * 8.0;
+            let layout = &self.layout;
+            let backdrop = layout.width.max(layout.height * 1.25);
+            let at = |pos: [f32; 2]| format!("translate({}, {})", pos[0], pos[1]);
             html!{
-                <g>
-                    <image width=1000, height=800, x=0, y=-20, href="landscape_2.jpg",></image>
-                    <g transform="translate(875, 400)",>{ battle.enemy_props.card.render(&self.ctx) }</g>
-                    { self.particles(&battle.particles) }
-                    <g transform="translate(750, 430)",>{ stats_card(&battle.enemy_props) }</g>
-                    <rect class="problem_card", x=250, y=20, width=500, height=500, rx=10, ry=10,></rect>
-                    <g transform="translate(500, 300) scale(5)",>
+                <g transform={format!("translate({}, {})", shake * self.ctx.wave(0.07), shake * self.ctx.wave(0.05))},>
+                    <image width=backdrop, height={backdrop * 0.8}, x={(layout.width - backdrop) / 2.0}, y=-20, href="landscape_2.jpg",></image>
+                    <g transform={format!("translate({}, {})", layout.enemy[0] + knockback, layout.enemy[1])}, class={if self.ctx.running(Event::HitFlash) { "enemy hit" } else { "enemy" }},>
+                        {
+                            if battle.won {
+                                html! { <g></g> }
+                            } else {
+                                battle.enemy_props.card.render(&self.ctx)
+                            }
+                        }
+                    </g>
+                    <g transform={format!("translate({}, {}) scale(4)", layout.fighter[0] + lunge, layout.fighter[1])}, class={if self.ctx.running(Event::Hurt) { "fighter hurt" } else { "fighter" }},>
+                        <g transform={format!("translate(0, {})", -jump)},>
+                            { render_avatar(&battle.land.player.appearance, Facing::Right, false, &self.ctx) }
+                        </g>
+                    </g>
+                    { if self.ctx.animations { battle.particles.render() } else { html! { <g></g> } } }
+                    <g transform={at(layout.stats)},>{ stats_card(&battle.enemy_props) }</g>
+                    <rect class="problem_card", x={layout.card[0]}, y={layout.card[1]}, width={layout.card[2]}, height={layout.card[3]}, rx=10, ry=10,></rect>
+                    <g transform={format!("{} scale({})", at(layout.problem), 5.0 * self.settings.text_size.scale())}, role="img",
+                        aria-label={battle.problem.as_ref().map_or(String::new(), |p| format!("Problem: {}", p.speech()))},>
                         {
                             if let Some(ref p) = battle.problem {
                                 p.render()
@@ -379,26 +1033,58 @@ impl Model {
                             }
                         }
                     </g>
-                    <g transform="translate(500, 560)",>
-                        { self.text_box() }
+                    { battle.texts.render(&self.ctx) }
+                    {
+                        if let Some(ref hint) = battle.hint {
+                            html! { <text class="hint", x={layout.hint[0]}, y={layout.hint[1]},>{ hint }</text> }
+                        } else {
+                            html! { <g></g> }
+                        }
+                    }
+                    <g transform={at(layout.answer)},>
+                        { self.text.render(&self.ctx) }
+                    </g>
+                    <g transform={at(layout.inventory)},>
+                        { battle.land.player.inventory.render() }
                     </g>
+                    <g transform={at(layout.gold)},>{ gold_counter(battle.land.player.gold) }</g>
                     <g transform="translate(0, 20)",>
-                        { health_bar(battle.state.health, 475.0) }
+                        {
+                            if battle.shield {
+                                html! { <circle class="shield", cx=45, cy=45, r=60,></circle> }
+                            } else {
+                                html! { <g></g> }
+                            }
+                        }
+                        { health_bar("Your health", battle.state.health, layout.bar_width as f64) }
                     </g>
-                    <g transform="scale(-1.0, 1.0) translate(-1000, 20)",>
-                        { health_bar(enemy_state.health, 475.0) }
+                    <g transform={at(layout.combo)},>{ combo_card(&battle.combo) }</g>
+                    <g transform={format!("scale(-1.0, 1.0) translate({}, 20)", -layout.width)},>
+                        {
+                            match battle.enemy.boss_phases() {
+                                Some(marks) => boss_health_bar(&battle.enemy_props.name, enemy_state.health, layout.bar_width as f64, &marks),
+                                None => health_bar(&battle.enemy_props.name, enemy_state.health, layout.bar_width as f64),
+                            }
+                        }
                     </g>
-                    <rect x={self.ctx.anim_t * 1000.0}, y=0, width=1000, height=600,></rect>
+                    {
+                        if battle.won {
+                            html! { <text class="victory", x={layout.victory[0]}, y={layout.victory[1]},>{"Victory!"}</text> }
+                        } else {
+                            html! { <g></g> }
+                        }
+                    }
                 </g>
             }
 
     }
     fn overland(&self) -> Html<Self> {
         let land = self.state.as_overland().unwrap();
+        let [cx, cy] = self.layout.center();
         html! {
-            <g transform="translate(500,300)",>
+            <g transform={format!("translate({},{})", cx, cy)},>
                 <g transform={format!("translate({},{})", -land.land_pos[0], land.land_pos[1])},>
-                    <image x=-1500, y=-1500, height=3000, width=3000, href="map.jpg",></image>
+                    { land.map.render(land.land_pos, [cx + 100.0, cy + 100.0]) }
                     {for land.obstacles.iter().map(|x| {
                         html! {
                             <g transform={format!("translate({}, {})", x.0[0], -x.0[1])},>
@@ -407,20 +1093,31 @@ impl Model {
                             </g>
                         }
                     })}
-                    { self.particles(&land.particles) }
+                    {for land.shops.iter().map(|site| site.render())}
+                    {for land.encounters.bosses.iter().map(|site| {
+                        html! {
+                            <g transform={format!("translate({}, {}) scale(0.5)", site.center.x, -site.center.y)},>
+                                { site.props.card.render(&self.ctx) }
+                            </g>
+                        }
+                    })}
+                    {for land.encounters.roamers.iter().map(|roamer| {
+                        html! {
+                            <g transform={format!("translate({}, {}) scale(0.3)", roamer.pos.x, -roamer.pos.y)},>
+                                { roamer.props.card.render(&self.ctx) }
+                            </g>
+                        }
+                    })}
+                    { if self.ctx.animations { land.particles.render() } else { html! { <g></g> } } }
                 </g>
-                <circle class="person", r=10, x=0, y=0,></circle>
-                <g transform="translate(0, 260)",>
-                    { self.text_box() }
+                { render_avatar(&land.player.appearance, land.facing, land.walking, &self.ctx) }
+                <g transform={format!("translate(0, {})", cy - 40.0)},>
+                    { self.text.render(&self.ctx) }
                 </g>
-            </g>
-        }
-    }
-    fn text_box(&self) -> Html<Self> {
-        html!{
-            <g>
-                <rect class="text_box", x=-250, y=-25, width=500, height=50, rx=10, ry=10,></rect>
-                <text x=-240, y=12, class="answertext",>{&self.text.text}</text>
+                <g transform={format!("translate({}, {})", 40.0 - cx, cy - 40.0)},>
+                    { land.player.inventory.render() }
+                </g>
+                <g transform={format!("translate({}, {})", 20.0 - cx, cy - 75.0)},>{ gold_counter(land.player.gold) }</g>
             </g>
         }
     }
@@ -437,14 +1134,57 @@ fn stats_card(props: &EnemyProps) -> Html<Model> {
     }
 }
 
-fn health_bar(health: f64, width: f64) -> Html<Model> {
+fn gold_counter(gold: usize) -> Html<Model> {
+    html! {
+        <text class="gold",>{ format!("{} gold", gold) }</text>
+    }
+}
+
+fn combo_card(combo: &Combo) -> Html<Model> {
+    html! {
+        <g class="combo",>
+            {
+                if combo.streak > 1 {
+                    html! { <text x=0, y=0, class="streak",>{ format!("{} hit combo", combo.streak) }</text> }
+                } else {
+                    html! { <g></g> }
+                }
+            }
+            <rect class="meter_back", x=0, y=10, width=150, height=10,></rect>
+            <rect class={if combo.meter >= 1.0 { "meter full" } else { "meter" }}, x=0, y=10, width={150.0 * combo.meter}, height=10,></rect>
+        </g>
+    }
+}
+
+/// A health bar, announced to screen readers as `name` and a percentage.
+fn health_bar(name: &str, health: f64, width: f64) -> Html<Model> {
     let start = 100.0 - 20.0;
     let end = width;
     let position = end*health + start*(1.0-health);
     html! {
-        <g class="health_bar",>
+        <g class="health_bar", role="img", aria-label={format!("{} {:.0}%", name, health.max(0.0) * 100.0)},>
             <path class="bar", d={format!("M {} 20 L {} 20 L {} 30 L {} 30", start, position, position-5.0, start-5.0) },></path>
             <path d="M0 0 L100 0 L50 100 L 0 100",></path>
         </g>
     }
 }
+
+/// A longer, heavier health bar with a notch at each phase threshold.
+fn boss_health_bar(name: &str, health: f64, width: f64, marks: &[f64]) -> Html<Model> {
+    let start = 100.0 - 20.0;
+    let end = width;
+    let at = |h: f64| end*h.max(0.0) + start*(1.0-h.max(0.0));
+    let position = at(health);
+    html! {
+        <g class="health_bar boss", role="img", aria-label={format!("{} {:.0}%", name, health.max(0.0) * 100.0)},>
+            <path class="bar", d={format!("M {} 15 L {} 15 L {} 35 L {} 35", start, position, position-10.0, start-10.0) },></path>
+            {for marks.iter().map(|&mark| {
+                html! {
+                    <line class="mark", x1={at(mark)}, y1=10, x2={at(mark)-12.0}, y2=40,></line>
+                }
+            })}
+            <path d="M0 0 L100 0 L50 100 L 0 100",></path>
+            <path class="crown", d="M15 85 L15 55 L30 70 L40 45 L50 70 L60 55 L55 85 Z",></path>
+        </g>
+    }
+}
@@ -0,0 +1,365 @@
+use yew::{html, Html};
+use rand::distributions::{Normal, Distribution};
+use rand::prelude::*;
+
+use crate::Model;
+
+/// The most particles alive in one system. Past this, new particles take the place of the ones
+/// closest to dying so that a big celebration never grows the pool.
+pub const MAX_PARTICLES: usize = 300;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Shape {
+    /// A dot with a trail behind it.
+    Spark,
+    Star,
+    /// A random digit.
+    Digit,
+    Confetti,
+    /// A soft dot drifting without a trail.
+    Mote,
+    Leaf,
+}
+
+/// How particles look over their life. Each particle takes a color from `palette` and blends
+/// toward `fade_to` as it ages, growing or shrinking from `size[0]` to `size[1]`.
+#[derive(Debug, Copy, Clone)]
+pub struct Style {
+    pub shape: Shape,
+    pub palette: &'static [[u8; 3]],
+    pub fade_to: Option<[u8; 3]>,
+    pub size: [f32; 2],
+    pub fade: bool,
+}
+
+const SPARK: Style = Style {
+    shape: Shape::Spark,
+    palette: &[[255, 238, 68]],
+    fade_to: None,
+    size: [5.0, 5.0],
+    fade: false,
+};
+
+const EMBER: Style = Style {
+    shape: Shape::Spark,
+    palette: &[[255, 238, 68]],
+    fade_to: Some([227, 93, 93]),
+    size: [5.0, 2.0],
+    fade: true,
+};
+
+const STAR: Style = Style {
+    shape: Shape::Star,
+    palette: &[[255, 238, 68], [255, 255, 255]],
+    fade_to: Some([242, 201, 76]),
+    size: [14.0, 4.0],
+    fade: true,
+};
+
+const CONFETTI: Style = Style {
+    shape: Shape::Confetti,
+    palette: &[[227, 93, 93], [74, 143, 201], [90, 132, 92], [242, 201, 76], [180, 110, 200]],
+    fade_to: None,
+    size: [10.0, 10.0],
+    fade: false,
+};
+
+const DIGIT: Style = Style {
+    shape: Shape::Digit,
+    palette: &[[255, 255, 255]],
+    fade_to: Some([242, 201, 76]),
+    size: [30.0, 18.0],
+    fade: true,
+};
+
+const POLLEN: Style = Style {
+    shape: Shape::Mote,
+    palette: &[[255, 250, 200], [255, 238, 150]],
+    fade_to: None,
+    size: [2.0, 3.0],
+    fade: true,
+};
+
+const LEAF: Style = Style {
+    shape: Shape::Leaf,
+    palette: &[[90, 132, 92], [140, 160, 70], [200, 150, 60]],
+    fade_to: None,
+    size: [6.0, 6.0],
+    fade: true,
+};
+
+pub struct Particle {
+    vel: [f32; 2],
+    pos: [f32; 2],
+    grav: [f32; 2],
+    damp: f32,
+    life: f32,
+    max_life: f32,
+    style: Style,
+    color: [u8; 3],
+    angle: f32,
+    spin: f32,
+    digit: u8,
+}
+
+impl Particle {
+    fn tick(&mut self, delta: f32) {
+        for i in 0..2 {
+            self.vel[i] += self.grav[i] * delta;
+            self.vel[i] *= 1.0/(self.damp*delta).exp2();
+            self.pos[i] += self.vel[i] * delta;
+        }
+        self.angle += self.spin * delta;
+        self.life -= delta;
+    }
+    /// How far through its life the particle is, from 0 to 1.
+    fn age(&self) -> f32 {
+        (1.0 - self.life / self.max_life).max(0.0).min(1.0)
+    }
+    fn render(&self) -> Html<Model> {
+        let age = self.age();
+        let size = self.style.size[0] + (self.style.size[1] - self.style.size[0]) * age;
+        let color = match self.style.fade_to {
+            Some(to) => {
+                let mix = |i: usize| (self.color[i] as f32 + (to[i] as f32 - self.color[i] as f32) * age) as u8;
+                [mix(0), mix(1), mix(2)]
+            },
+            None => self.color,
+        };
+        let opacity = if self.style.fade { 1.0 - age } else { 1.0 };
+        let style = format!("fill: rgb({}, {}, {}); stroke: rgb({}, {}, {}); opacity: {}",
+            color[0], color[1], color[2], color[0], color[1], color[2], opacity);
+        let transform = format!("translate({}, {}) rotate({})", self.pos[0], self.pos[1], self.angle);
+        match self.style.shape {
+            Shape::Spark => html! {
+                <g class="particle", style=style,>
+                    <circle cx={self.pos[0]}, cy={self.pos[1]}, r=size,></circle>
+                    <line x1={self.pos[0]},
+                    y1={self.pos[1]},
+                    x2={self.pos[0]-self.vel[0]*0.05},
+                    y2={self.pos[1]-self.vel[1]*0.05},></line>
+                </g>
+            },
+            Shape::Star => html! {
+                <path class="particle star", style=style, transform=transform,
+                    d={format!("M 0 {} L {} {} L {} {} L {} {} L {} {} Z",
+                        -size, size * 0.59, size * 0.81, -size * 0.95, -size * 0.31, size * 0.95, -size * 0.31, -size * 0.59, size * 0.81)},></path>
+            },
+            Shape::Digit => html! {
+                <text class="particle digit", style={format!("{}; font-size: {}px", style, size)}, transform=transform,>{ self.digit }</text>
+            },
+            Shape::Confetti => html! {
+                <rect class="particle confetti", style=style, transform=transform, x={-size / 2.0}, y={-size / 4.0}, width=size, height={size / 2.0},></rect>
+            },
+            Shape::Mote => html! {
+                <circle class="particle mote", style=style, cx={self.pos[0]}, cy={self.pos[1]}, r=size,></circle>
+            },
+            Shape::Leaf => html! {
+                <ellipse class="particle leaf", style=style, transform=transform, cx=0, cy=0, rx=size, ry={size / 2.5},></ellipse>
+            },
+        }
+    }
+}
+
+/// Spawns particles in a box of half size `area` around `pos`, with velocities spread normally
+/// around `vel`. `count` particles come out at once, then `rate` more every second until
+/// `duration` runs out; a stream with no duration runs for as long as it is kept.
+#[derive(Debug, Clone)]
+pub struct Emitter {
+    pub pos: [f32; 2],
+    pub area: [f32; 2],
+    pub vel: [f32; 2],
+    pub spread: f32,
+    pub grav: [f32; 2],
+    pub damp: f32,
+    pub life: f32,
+    pub spin: f32,
+    pub style: Style,
+    pub count: usize,
+    pub rate: f32,
+    pub duration: Option<f32>,
+    carry: f32,
+}
+
+impl Emitter {
+    pub fn burst(pos: [f32; 2], vel: [f32; 2], spread: f32, count: usize, life: f32, style: Style) -> Self {
+        Emitter {
+            pos: pos,
+            area: [0.0, 0.0],
+            vel: vel,
+            spread: spread,
+            grav: [0.0, 0.0],
+            damp: 0.0,
+            life: life,
+            spin: 0.0,
+            style: style,
+            count: count,
+            rate: 0.0,
+            duration: Some(0.0),
+            carry: 0.0,
+        }
+    }
+    pub fn stream(pos: [f32; 2], area: [f32; 2], rate: f32, life: f32, style: Style) -> Self {
+        Emitter {
+            area: area,
+            rate: rate,
+            count: 0,
+            duration: None,
+            ..Emitter::burst(pos, [0.0, 0.0], 0.0, 0, life, style)
+        }
+    }
+    pub fn gravity(self, grav: [f32; 2], damp: f32) -> Self {
+        Emitter {
+            grav: grav,
+            damp: damp,
+            ..self
+        }
+    }
+    pub fn spin(self, spin: f32) -> Self {
+        Emitter {
+            spin: spin,
+            ..self
+        }
+    }
+    pub fn lasting(self, duration: f32) -> Self {
+        Emitter {
+            duration: Some(duration),
+            ..self
+        }
+    }
+    fn finished(&self) -> bool {
+        self.duration.map(|d| d <= 0.0).unwrap_or(false)
+    }
+    /// How many particles the stream releases over the next `delta` seconds.
+    fn due(&mut self, delta: f32) -> usize {
+        let delta = self.duration.map(|d| delta.min(d.max(0.0))).unwrap_or(delta);
+        if let Some(ref mut d) = self.duration {
+            *d -= delta;
+        }
+        self.carry += self.rate * delta;
+        let due = self.carry.floor();
+        self.carry -= due;
+        due as usize
+    }
+}
+
+/// A capped pool of particles plus the emitters still streaming into it.
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    emitters: Vec<Emitter>,
+    rng: SmallRng,
+}
+
+impl ParticleSystem {
+    pub fn new() -> Self {
+        ParticleSystem {
+            particles: Vec::with_capacity(MAX_PARTICLES),
+            emitters: Vec::new(),
+            rng: SmallRng::from_entropy(),
+        }
+    }
+    fn spawn(&mut self, emitter: &Emitter, count: usize) {
+        let xdst = Normal::new(emitter.vel[0] as f64, emitter.spread as f64);
+        let ydst = Normal::new(emitter.vel[1] as f64, emitter.spread as f64);
+        for _ in 0..count {
+            let rng = &mut self.rng;
+            let jitter = |rng: &mut SmallRng, half: f32| if half > 0.0 { rng.gen_range(-half, half) } else { 0.0 };
+            let particle = Particle {
+                pos: [emitter.pos[0] + jitter(rng, emitter.area[0]), emitter.pos[1] + jitter(rng, emitter.area[1])],
+                vel: [xdst.sample(rng) as f32, ydst.sample(rng) as f32],
+                grav: emitter.grav,
+                damp: emitter.damp,
+                life: emitter.life,
+                max_life: emitter.life,
+                style: emitter.style,
+                color: *emitter.style.palette.choose(rng).unwrap_or(&[255, 255, 255]),
+                angle: rng.gen_range(0.0, 360.0),
+                spin: jitter(rng, emitter.spin),
+                digit: rng.gen_range(0, 10),
+            };
+            if self.particles.len() < MAX_PARTICLES {
+                self.particles.push(particle);
+            } else {
+                let oldest = (0..self.particles.len())
+                    .min_by(|&a, &b| self.particles[a].life.partial_cmp(&self.particles[b].life).unwrap())
+                    .unwrap();
+                self.particles[oldest] = particle;
+            }
+        }
+    }
+    /// Releases the emitter's burst now and keeps it around if it streams.
+    pub fn emit(&mut self, emitter: Emitter) {
+        self.spawn(&emitter, emitter.count);
+        if emitter.rate > 0.0 && !emitter.finished() {
+            self.emitters.push(emitter);
+        }
+    }
+    /// Runs an emitter owned by someone else, such as ambient effects that follow the camera.
+    pub fn stream(&mut self, emitter: &mut Emitter, delta: f32) {
+        let due = emitter.due(delta);
+        self.spawn(emitter, due);
+    }
+    pub fn tick(&mut self, delta: f32) {
+        for particle in self.particles.iter_mut() {
+            particle.tick(delta);
+        }
+        for i in (0..self.particles.len()).rev() {
+            if self.particles[i].life < 0.0 {
+                self.particles.swap_remove(i);
+            }
+        }
+        let mut emitters = std::mem::replace(&mut self.emitters, Vec::new());
+        for emitter in emitters.iter_mut() {
+            self.stream(emitter, delta);
+        }
+        emitters.retain(|e| !e.finished());
+        self.emitters = emitters;
+    }
+    pub fn render(&self) -> Html<Model> {
+        html! {
+            <g class="particles",>
+                {for self.particles.iter().map(|particle| particle.render())}
+            </g>
+        }
+    }
+}
+
+pub fn blast(pos: [f32; 2], vel: [f32; 2], spread: f32, count: usize, life: f32) -> Emitter {
+    Emitter::burst(pos, vel, spread, count, life, SPARK).gravity([0.0, 400.0], 0.0)
+}
+
+pub fn firework(pos: [f32; 2], vel: [f32; 2], spread: f32, count: usize, life: f32) -> Emitter {
+    Emitter::burst(pos, vel, spread, count, life, EMBER).gravity([0.0, 0.0], 10.0)
+}
+
+pub fn stars(pos: [f32; 2], spread: f32, count: usize) -> Emitter {
+    Emitter::burst(pos, [0.0, 0.0], spread, count, 1.2, STAR).gravity([0.0, 0.0], 6.0).spin(360.0)
+}
+
+pub fn digits(pos: [f32; 2], count: usize) -> Emitter {
+    Emitter::burst(pos, [0.0, -300.0], 200.0, count, 1.0, DIGIT).gravity([0.0, 600.0], 1.0).spin(180.0)
+}
+
+/// Falls from the top of a screen `width` wide for a few seconds.
+pub fn confetti(width: f32, rate: f32, duration: f32) -> Emitter {
+    Emitter::stream([width / 2.0, -20.0], [width / 2.0, 10.0], rate, 4.0, CONFETTI)
+        .gravity([0.0, 120.0], 1.0)
+        .spin(540.0)
+        .lasting(duration)
+}
+
+/// Pollen drifting around the player on the overland, kept centred on them by the caller.
+pub fn pollen() -> Emitter {
+    let mut emitter = Emitter::stream([0.0, 0.0], [550.0, 350.0], 8.0, 5.0, POLLEN);
+    emitter.vel = [15.0, -5.0];
+    emitter.spread = 10.0;
+    emitter
+}
+
+/// Leaves blowing in along a line; the caller keeps it just above the screen.
+pub fn leaves() -> Emitter {
+    let mut emitter = Emitter::stream([0.0, 0.0], [550.0, 20.0], 1.5, 8.0, LEAF).spin(90.0);
+    emitter.vel = [30.0, 50.0];
+    emitter.spread = 15.0;
+    emitter
+}
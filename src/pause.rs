@@ -0,0 +1,93 @@
+use yew::{html, Html};
+
+use crate::avatar::step;
+use crate::bindings::*;
+use crate::settings::*;
+use crate::Model;
+
+const ROWS: usize = 11;
+
+/// What the game should do after a key press in the pause menu.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PauseChoice {
+    Stay,
+    Resume,
+    Controls,
+    QuitToTitle,
+}
+
+/// Shown over the game while simulation ticks are frozen.
+pub struct PauseMenu {
+    pub row: usize,
+}
+
+impl PauseMenu {
+    pub fn new() -> Self {
+        PauseMenu {
+            row: 0,
+        }
+    }
+    /// Changes the setting on the selected row `by` steps.
+    fn adjust(&mut self, settings: &mut Settings, by: isize) {
+        let turn = |volume: u8| (volume as isize + by).max(0).min(MAX_VOLUME as isize) as u8;
+        match self.row {
+            1 => settings.volume = turn(settings.volume),
+            2 => settings.effects_volume = turn(settings.effects_volume),
+            3 => settings.music_volume = turn(settings.music_volume),
+            4 => settings.difficulty = step(&Difficulty::ALL, settings.difficulty, by),
+            5 => settings.text_size = step(&TextSize::ALL, settings.text_size, by),
+            6 => settings.theme = step(&Theme::ALL, settings.theme, by),
+            7 => settings.animations = !settings.animations,
+            8 => settings.read_aloud = !settings.read_aloud,
+            _ => (),
+        }
+    }
+    pub fn key(&mut self, action: Option<Action>, settings: &mut Settings) -> PauseChoice {
+        match action {
+            Some(Action::Up) => self.row = (self.row + ROWS - 1) % ROWS,
+            Some(Action::Down) => self.row = (self.row + 1) % ROWS,
+            Some(Action::Left) => self.adjust(settings, -1),
+            Some(Action::Right) => self.adjust(settings, 1),
+            Some(Action::Back) | Some(Action::Pause) => return PauseChoice::Resume,
+            Some(Action::Submit) => match self.row {
+                0 => return PauseChoice::Resume,
+                9 => return PauseChoice::Controls,
+                10 => return PauseChoice::QuitToTitle,
+                _ => self.adjust(settings, 1),
+            },
+            _ => (),
+        }
+        PauseChoice::Stay
+    }
+    pub fn render(&self, settings: &Settings) -> Html<Model> {
+        let bar = |volume: u8| (0..MAX_VOLUME).map(|i| if i < volume { '█' } else { '░' }).collect::<String>();
+        let rows = [
+            "Resume".to_string(),
+            format!("◀ Volume {} ▶", bar(settings.volume)),
+            format!("◀ Effects {} ▶", bar(settings.effects_volume)),
+            format!("◀ Music {} ▶", bar(settings.music_volume)),
+            format!("◀ Difficulty: {} ▶", settings.difficulty.name()),
+            format!("◀ Text size: {} ▶", settings.text_size.name()),
+            format!("◀ Theme: {} ▶", settings.theme.name()),
+            format!("◀ Reduced motion: {} ▶", if settings.animations { "Off" } else { "On" }),
+            format!("◀ Read aloud: {} ▶", if settings.read_aloud { "On" } else { "Off" }),
+            "Controls".to_string(),
+            "Quit to title".to_string(),
+        ];
+        html! {
+            <g class="pause",>
+                <rect class="problem_card", x=250, y=20, width=500, height=560, rx=10, ry=10,></rect>
+                <text class="title", x=500, y=75,>{"Paused"}</text>
+                {for rows.iter().enumerate().map(|(i, label)| {
+                    let y = 100 + i as i32 * 42;
+                    html! {
+                        <g class={if i == self.row { "option selected" } else { "option" }},>
+                            <rect x=300, y=y, width=400, height=36, rx=5, ry=5,></rect>
+                            <text x=500, y={y + 26},>{ label }</text>
+                        </g>
+                    }
+                })}
+            </g>
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use crate::items::*;
+use crate::avatar::*;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Hat {
+    Cap,
+    Wizard,
+    Crown,
+}
+
+/// Purely visual upgrades for the player's avatar.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Cosmetic {
+    Color(&'static str),
+    Hat(Hat),
+}
+
+/// Everything the player carries from one battle to the next.
+pub struct Player {
+    pub inventory: Inventory,
+    pub gold: usize,
+    pub unlocked: Vec<Cosmetic>,
+    pub appearance: Appearance,
+}
+
+impl Player {
+    pub fn new() -> Self {
+        Player {
+            inventory: Inventory::new(),
+            gold: 0,
+            unlocked: vec![Cosmetic::Color("#e0861a"), Cosmetic::Color("#e35d5d")],
+            appearance: Appearance::new(),
+        }
+    }
+    pub fn spend(&mut self, price: usize) -> bool {
+        if self.gold >= price {
+            self.gold -= price;
+            true
+        } else {
+            false
+        }
+    }
+}
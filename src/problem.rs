@@ -1,11 +1,25 @@
 use yew::{html, Html};
 use rand::prelude::*;
 use crate::Model;
+use crate::textbox::InputFilter;
+use crate::words::*;
 
 pub trait Problem {
     fn render(&self) -> Html<Model>;
+    /// The problem as it should be read aloud.
+    fn speech(&self) -> String;
     fn test_correct(&self, answer: &str) -> bool;
     fn get_answer(&self) -> String;
+    fn hint(&self) -> String {
+        match self.get_answer().chars().next() {
+            Some(c) => format!("The answer starts with {}", c),
+            None => "No hint for this one".into(),
+        }
+    }
+    /// What may be typed into the answer box.
+    fn input_filter(&self) -> InputFilter {
+        InputFilter::for_answer(&self.get_answer())
+    }
 }
 
 pub struct TextProblem {
@@ -19,6 +33,9 @@ impl Problem for TextProblem {
             <text class="textproblemtext",>{ &self.problem }</text>
         }
     }
+    fn speech(&self) -> String {
+        expression_words(&self.problem)
+    }
     fn test_correct(&self, answer: &str) -> bool {
         self.answer == answer
     }
@@ -36,8 +53,7 @@ impl TextProblem {
     }
 }
 
-pub fn gen_simple_add_sub(start: usize, end: usize) -> TextProblem {
-    let mut rng = SmallRng::from_entropy();
+pub fn gen_simple_add_sub<R: Rng + ?Sized>(rng: &mut R, start: usize, end: usize) -> TextProblem {
     let (a, b) = (rng.gen_range(start, end), rng.gen_range(start, end));
     let c = a+b;
     if rng.gen::<bool>() {
@@ -52,3 +68,129 @@ pub fn gen_simple_add_sub(start: usize, end: usize) -> TextProblem {
         }
     }
 }
+
+pub fn gen_multiplication<R: Rng + ?Sized>(rng: &mut R, max_factor: usize) -> TextProblem {
+    let (a, b) = (rng.gen_range(2, max_factor + 1), rng.gen_range(2, max_factor + 1));
+    TextProblem {
+        problem: format!("{}×{}=?", a, b),
+        answer: format!("{}", a*b),
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 { a.abs() } else { gcd(b, a % b) }
+}
+
+fn reduce(n: i64, d: i64) -> (i64, i64) {
+    let g = gcd(n, d).max(1);
+    if d < 0 { (-n/g, -d/g) } else { (n/g, d/g) }
+}
+
+/// Reads `n/d` or a whole number.
+fn parse_fraction(text: &str) -> Option<(i64, i64)> {
+    let mut parts = text.trim().splitn(2, '/');
+    let n = parts.next()?.trim().parse::<i64>().ok()?;
+    let d = match parts.next() {
+        Some(d) => d.trim().parse::<i64>().ok()?,
+        None => 1,
+    };
+    if d == 0 { None } else { Some(reduce(n, d)) }
+}
+
+/// A problem whose answer is a fraction; any equivalent fraction is accepted.
+pub struct FractionProblem {
+    pub problem: String,
+    pub answer: (i64, i64),
+}
+
+impl Problem for FractionProblem {
+    fn render(&self) -> Html<Model> {
+        html! {
+            <text class="textproblemtext",>{ &self.problem }</text>
+        }
+    }
+    fn speech(&self) -> String {
+        expression_words(&self.problem)
+    }
+    fn test_correct(&self, answer: &str) -> bool {
+        parse_fraction(answer) == Some(self.answer)
+    }
+    fn get_answer(&self) -> String {
+        match self.answer {
+            (n, 1) => format!("{}", n),
+            (n, d) => format!("{}/{}", n, d),
+        }
+    }
+    fn input_filter(&self) -> InputFilter {
+        InputFilter::Fraction
+    }
+}
+
+/// Adds two fractions. Low levels keep the denominators equal.
+pub fn gen_fraction_add<R: Rng + ?Sized>(rng: &mut R, level: usize) -> FractionProblem {
+    let denominators = [2, 3, 4, 5, 6, 8, 10];
+    let choices = &denominators[..(level + 2).min(denominators.len())];
+    let b = *choices.choose(rng).unwrap();
+    let d = if level <= 2 { b } else { *choices.choose(rng).unwrap() };
+    let (a, c) = (rng.gen_range(1, b), rng.gen_range(1, d));
+    FractionProblem {
+        problem: format!("{}/{}+{}/{}=?", a, b, c, d),
+        answer: reduce(a*d + c*b, b*d),
+    }
+}
+
+/// Reading the time off an analog clock face, answered as `h:mm`.
+pub struct ClockProblem {
+    pub hour: u32,
+    pub minute: u32,
+}
+
+impl Problem for ClockProblem {
+    fn render(&self) -> Html<Model> {
+        let minute_angle = self.minute as f32 / 60.0 * 2.0 * std::f32::consts::PI;
+        let hour_angle = (self.hour as f32 + self.minute as f32 / 60.0) / 12.0 * 2.0 * std::f32::consts::PI;
+        html! {
+            <g class="clock",>
+                <circle class="face", cx=0, cy=-10, r=35,></circle>
+                {for (0..12).map(|i| {
+                    let angle = i as f32 / 12.0 * 2.0 * std::f32::consts::PI;
+                    html! {
+                        <line class="tick", x1={angle.sin()*30.0}, y1={-10.0-angle.cos()*30.0},
+                            x2={angle.sin()*35.0}, y2={-10.0-angle.cos()*35.0},></line>
+                    }
+                })}
+                <line class="hour", x1=0, y1=-10, x2={hour_angle.sin()*18.0}, y2={-10.0-hour_angle.cos()*18.0},></line>
+                <line class="minute", x1=0, y1=-10, x2={minute_angle.sin()*28.0}, y2={-10.0-minute_angle.cos()*28.0},></line>
+                <text class="textproblemtext", y=40,>{ "What time?" }</text>
+            </g>
+        }
+    }
+    fn speech(&self) -> String {
+        "What time does the clock show?".into()
+    }
+    fn test_correct(&self, answer: &str) -> bool {
+        let mut parts = answer.trim().splitn(2, ':');
+        let hour = parts.next().and_then(|h| h.parse::<u32>().ok());
+        let minute = parts.next().filter(|m| m.len() == 2).and_then(|m| m.parse::<u32>().ok());
+        hour == Some(self.hour) && minute == Some(self.minute)
+    }
+    fn get_answer(&self) -> String {
+        format!("{}:{:02}", self.hour, self.minute)
+    }
+    fn hint(&self) -> String {
+        format!("The short hand points near {}", self.hour)
+    }
+}
+
+/// Whole and half hours at first, then quarters, then any five minutes.
+pub fn gen_clock<R: Rng + ?Sized>(rng: &mut R, level: usize) -> ClockProblem {
+    let step = match level {
+        0...2 => 30,
+        3...4 => 15,
+        _ => 5,
+    };
+    ClockProblem {
+        hour: rng.gen_range(1, 13),
+        minute: rng.gen_range(0, 60 / step) * step,
+    }
+}
@@ -0,0 +1,170 @@
+use std::fmt::Write;
+
+pub const REPLAY_KEY: &str = "mathland.replay";
+
+const HEADER: &str = "mathland-replay 1";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputEvent {
+    Tick(f32),
+    KeyDown(String),
+    KeyUp(String),
+    /// The analog movement vector from a gamepad stick.
+    Stick(f32, f32),
+}
+
+/// A recorded session: the rng seed the session started from and every input event, stamped
+/// with the number of ticks that came before it, so playback lands on the same tick exactly.
+pub struct Recording {
+    pub seed: u64,
+    pub events: Vec<(u64, InputEvent)>,
+    ticks: u64,
+}
+
+impl Recording {
+    pub fn new(seed: u64) -> Self {
+        Recording {
+            seed: seed,
+            events: Vec::new(),
+            ticks: 0,
+        }
+    }
+    pub fn push(&mut self, event: InputEvent) {
+        let tick = self.ticks;
+        if let InputEvent::Tick(_) = event {
+            self.ticks += 1;
+        }
+        self.events.push((tick, event));
+    }
+    /// One event per line, `<tick> <kind> <payload>`, where kind is `t` (tick, payload is the
+    /// delta), `d` (key down), `u` (key up, payload is the key string) or `s` (stick, payload is
+    /// `x y`).
+    pub fn to_log(&self) -> String {
+        let mut log = format!("{} {}\n", HEADER, self.seed);
+        for (tick, event) in self.events.iter() {
+            let _ = match event {
+                InputEvent::Tick(delta) => writeln!(log, "{} t {}", tick, delta),
+                InputEvent::KeyDown(key) => writeln!(log, "{} d {}", tick, key),
+                InputEvent::KeyUp(key) => writeln!(log, "{} u {}", tick, key),
+                InputEvent::Stick(x, y) => writeln!(log, "{} s {} {}", tick, x, y),
+            };
+        }
+        log
+    }
+    pub fn from_log(log: &str) -> Result<Self, String> {
+        let mut lines = log.lines();
+        let seed = match lines.next() {
+            Some(header) if header.starts_with(HEADER) => {
+                header[HEADER.len()..].trim().parse::<u64>().map_err(|e| format!("bad seed: {}", e))?
+            },
+            _ => return Err("missing replay header".into()),
+        };
+        let mut recording = Recording::new(seed);
+        for (i, line) in lines.enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.splitn(3, ' ');
+            let (tick, kind, payload) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(tick), Some(kind), Some(payload)) => (tick, kind, payload),
+                _ => return Err(format!("line {}: malformed event", i + 2)),
+            };
+            let tick = tick.parse::<u64>().map_err(|e| format!("line {}: {}", i + 2, e))?;
+            let event = match kind {
+                "t" => InputEvent::Tick(payload.parse::<f32>().map_err(|e| format!("line {}: {}", i + 2, e))?),
+                "d" => InputEvent::KeyDown(payload.into()),
+                "u" => InputEvent::KeyUp(payload.into()),
+                "s" => {
+                    let mut axes = payload.splitn(2, ' ').map(|a| a.parse::<f32>());
+                    match (axes.next(), axes.next()) {
+                        (Some(Ok(x)), Some(Ok(y))) => InputEvent::Stick(x, y),
+                        _ => return Err(format!("line {}: bad stick {:?}", i + 2, payload)),
+                    }
+                },
+                x => return Err(format!("line {}: unknown event kind {:?}", i + 2, x)),
+            };
+            if let InputEvent::Tick(_) = event {
+                recording.ticks = tick + 1;
+            }
+            recording.events.push((tick, event));
+        }
+        Ok(recording)
+    }
+}
+
+/// Feeds a recording back out one recorded tick for every tick of the running game.
+pub struct Playback {
+    pub recording: Recording,
+    next: usize,
+    ticks: u64,
+}
+
+impl Playback {
+    pub fn new(recording: Recording) -> Self {
+        Playback {
+            recording: recording,
+            next: 0,
+            ticks: 0,
+        }
+    }
+    /// The events up to and including the next recorded tick.
+    pub fn advance(&mut self) -> Vec<InputEvent> {
+        let mut due = Vec::new();
+        while let Some((tick, event)) = self.recording.events.get(self.next) {
+            if *tick > self.ticks {
+                break;
+            }
+            due.push(event.clone());
+            self.next += 1;
+        }
+        self.ticks += 1;
+        due
+    }
+    pub fn finished(&self) -> bool {
+        self.next >= self.recording.events.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> Recording {
+        let mut recording = Recording::new(42);
+        recording.push(InputEvent::Tick(1.0 / 60.0));
+        recording.push(InputEvent::KeyDown("ArrowUp".into()));
+        recording.push(InputEvent::Tick(1.0 / 60.0));
+        recording.push(InputEvent::Stick(0.25, -1.0 / 3.0));
+        recording.push(InputEvent::KeyUp(" ".into()));
+        recording.push(InputEvent::Tick(1.0 / 60.0));
+        recording
+    }
+
+    #[test]
+    fn round_trip() {
+        let recording = session();
+        let read = Recording::from_log(&recording.to_log()).unwrap();
+        assert_eq!(read.seed, 42);
+        assert_eq!(read.events, recording.events);
+        assert_eq!(read.ticks, recording.ticks);
+    }
+
+    #[test]
+    fn playback_keeps_events_on_their_tick() {
+        let mut playback = Playback::new(session());
+        assert_eq!(playback.advance(), vec![InputEvent::Tick(1.0 / 60.0)]);
+        assert_eq!(playback.advance().len(), 2);
+        assert_eq!(playback.advance().len(), 3);
+        assert!(playback.finished());
+    }
+
+    #[test]
+    fn malformed_lines() {
+        assert!(Recording::from_log("").is_err());
+        assert!(Recording::from_log("mathland-replay 1 seed").is_err());
+        for line in &["0 t", "x t 0.1", "0 t fast", "0 s 1", "0 q 1", "-1 d a"] {
+            let log = format!("{} 7\n{}\n", HEADER, line);
+            assert!(Recording::from_log(&log).is_err(), "{:?} should not parse", line);
+        }
+    }
+}
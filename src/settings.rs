@@ -0,0 +1,156 @@
+use serde_derive::{Deserialize, Serialize};
+use yew::format::Text;
+use yew::services::storage::StorageService;
+
+use crate::bindings::*;
+
+pub const SETTINGS_KEY: &str = "mathland.settings";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+    /// Scales the damage the player takes.
+    pub fn damage_taken(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextSize {
+    Small,
+    Normal,
+    Large,
+}
+
+impl TextSize {
+    pub const ALL: [TextSize; 3] = [TextSize::Small, TextSize::Normal, TextSize::Large];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TextSize::Small => "Small",
+            TextSize::Normal => "Normal",
+            TextSize::Large => "Large",
+        }
+    }
+    /// How much larger problems are drawn than usual.
+    pub fn scale(&self) -> f32 {
+        match self {
+            TextSize::Small => 0.8,
+            TextSize::Normal => 1.0,
+            TextSize::Large => 1.3,
+        }
+    }
+    /// The class on the root `<svg>` that resizes labels and messages.
+    pub fn class(&self) -> &'static str {
+        match self {
+            TextSize::Small => "text_small",
+            TextSize::Normal => "text_normal",
+            TextSize::Large => "text_large",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    Standard,
+    HighContrast,
+    /// A plainer, more widely spaced font for readers with dyslexia.
+    Dyslexia,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Standard, Theme::HighContrast, Theme::Dyslexia];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Standard => "Standard",
+            Theme::HighContrast => "High contrast",
+            Theme::Dyslexia => "Dyslexia friendly",
+        }
+    }
+    /// The class on the root `<svg>` that restyles the game.
+    pub fn class(&self) -> &'static str {
+        match self {
+            Theme::Standard => "theme_standard",
+            Theme::HighContrast => "theme_contrast",
+            Theme::Dyslexia => "theme_dyslexia",
+        }
+    }
+}
+
+pub const MAX_VOLUME: u8 = 10;
+
+/// Player preferences, kept in local storage between visits. Anything missing from what was
+/// stored, such as settings added since, takes its default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub bindings: Bindings,
+    /// The master volume, from 0 to `MAX_VOLUME` like the channels under it.
+    pub volume: u8,
+    pub effects_volume: u8,
+    pub music_volume: u8,
+    pub difficulty: Difficulty,
+    pub text_size: TextSize,
+    pub theme: Theme,
+    /// Off for reduced motion: no particles, flicker, shaking or transitions.
+    pub animations: bool,
+    /// Whether battle problems are read aloud.
+    pub read_aloud: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings::new()
+    }
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        Settings {
+            bindings: Bindings::arrows(),
+            volume: 7,
+            effects_volume: 8,
+            music_volume: 5,
+            difficulty: Difficulty::Normal,
+            text_size: TextSize::Normal,
+            theme: Theme::Standard,
+            animations: true,
+            read_aloud: false,
+        }
+    }
+    /// The master, effects and music volumes from 0 to 1.
+    pub fn volumes(&self) -> (f32, f32, f32) {
+        let scale = |volume: u8| volume as f32 / MAX_VOLUME as f32;
+        (scale(self.volume), scale(self.effects_volume), scale(self.music_volume))
+    }
+    /// The stored settings, or the defaults if there are none or they can't be read.
+    pub fn load(storage: &mut StorageService) -> Self {
+        let stored: Text = storage.restore(SETTINGS_KEY);
+        stored.ok().and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_else(Settings::new)
+    }
+    pub fn save(&self, storage: &mut StorageService) {
+        if let Ok(json) = serde_json::to_string(self) {
+            let value: Text = Ok(json);
+            storage.store(SETTINGS_KEY, value);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use yew::{html, Html};
+use cgmath::Vector2;
+
+use crate::bindings::*;
+use crate::items::*;
+use crate::player::*;
+use crate::terrain::*;
+use crate::tilemap::*;
+use crate::{Model, Overland};
+
+pub enum Ware {
+    Item(Item),
+    Cosmetic(&'static str, Cosmetic),
+}
+
+pub const WARES: &[(Ware, usize)] = &[
+    (Ware::Item(Item::Potion), 10),
+    (Ware::Item(Item::HintToken), 8),
+    (Ware::Item(Item::SkipScroll), 15),
+    (Ware::Item(Item::Shield), 20),
+    (Ware::Cosmetic("Blue Paint", Cosmetic::Color("#4a8fc9")), 25),
+    (Ware::Cosmetic("Green Paint", Cosmetic::Color("#5a845c")), 25),
+    (Ware::Cosmetic("Cap", Cosmetic::Hat(Hat::Cap)), 30),
+    (Ware::Cosmetic("Wizard Hat", Cosmetic::Hat(Hat::Wizard)), 60),
+    (Ware::Cosmetic("Crown", Cosmetic::Hat(Hat::Crown)), 120),
+];
+
+impl Ware {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Ware::Item(item) => item.name(),
+            Ware::Cosmetic(name, _) => *name,
+        }
+    }
+}
+
+/// A spot on the overland map that opens the shop, from the map's `shops` object layer.
+pub struct ShopSite {
+    pub area: Vec<Vector2<f32>>,
+    pub center: Vector2<f32>,
+}
+
+impl ShopSite {
+    pub fn from_map(map: &TileMap) -> Vec<Self> {
+        map.objects.iter().filter(|o| o.layer == "shops").map(|o| {
+            ShopSite {
+                area: o.points.clone(),
+                center: o.center(),
+            }
+        }).collect()
+    }
+    pub fn contains(&self, pos: Vector2<f32>) -> bool {
+        point_in_polygon(&self.area, pos)
+    }
+    pub fn render(&self) -> Html<Model> {
+        html! {
+            <g class="shop_site", transform={format!("translate({}, {})", self.center.x, -self.center.y)},>
+                <path d="M -45 0 L -45 -50 L 0 -85 L 45 -50 L 45 0 Z",></path>
+                <rect class="door", x=-12, y=-30, width=24, height=30,></rect>
+                <text x=0, y=-55,>{"SHOP"}</text>
+            </g>
+        }
+    }
+}
+
+pub struct Shop {
+    pub land: Overland,
+    pub selected: usize,
+    pub message: Option<String>,
+}
+
+impl Shop {
+    pub fn new(land: Overland) -> Self {
+        Shop {
+            land: land,
+            selected: 0,
+            message: None,
+        }
+    }
+    pub fn up(&mut self) {
+        self.selected = (self.selected + WARES.len() - 1) % WARES.len();
+    }
+    pub fn down(&mut self) {
+        self.selected = (self.selected + 1) % WARES.len();
+    }
+    pub fn buy(&mut self) {
+        let player = &mut self.land.player;
+        let (ware, price) = &WARES[self.selected];
+        if let Ware::Cosmetic(_, cosmetic) = ware {
+            if player.unlocked.contains(cosmetic) {
+                self.message = Some(format!("You already own the {}", ware.name()));
+                return;
+            }
+        }
+        if !player.spend(*price) {
+            self.message = Some(format!("The {} costs {} gold", ware.name(), price));
+            return;
+        }
+        match ware {
+            Ware::Item(item) => player.inventory.add(*item),
+            Ware::Cosmetic(_, cosmetic) => player.unlocked.push(*cosmetic),
+        }
+        self.message = Some(format!("Bought the {}", ware.name()));
+    }
+    pub fn render(&self, bindings: &Bindings) -> Html<Model> {
+        let player = &self.land.player;
+        let look = bindings.keys_for(Action::Look).first()
+            .map(|key| format!("{} for your look, ", key_label(key).to_uppercase()))
+            .unwrap_or_default();
+        html! {
+            <g class="shop",>
+                <rect class="problem_card", x=150, y=20, width=700, height=500, rx=10, ry=10,></rect>
+                <text class="title", x=500, y=80,>{"Shop"}</text>
+                <text class="gold", x=820, y=80,>{ format!("{} gold", player.gold) }</text>
+                {for WARES.iter().enumerate().map(|(i, (ware, price))| {
+                    let owned = match ware {
+                        Ware::Item(item) => format!("have {}", player.inventory.count(*item)),
+                        Ware::Cosmetic(_, cosmetic) if player.unlocked.contains(cosmetic) => "owned".into(),
+                        Ware::Cosmetic(..) => "".into(),
+                    };
+                    let y = 130 + i * 40;
+                    html! {
+                        <g class={if i == self.selected { "ware selected" } else { "ware" }},>
+                            <rect x=180, y={y - 28}, width=640, height=36, rx=5, ry=5,></rect>
+                            <text x=200, y=y,>{ ware.name() }</text>
+                            <text class="owned", x=600, y=y,>{ owned }</text>
+                            <text class="price", x=800, y=y,>{ format!("{} gold", price) }</text>
+                        </g>
+                    }
+                })}
+                <text class="message", x=500, y=500,>{ self.message.clone().unwrap_or_else(|| format!("Arrows to choose, Enter to buy, {}Escape to leave", look)) }</text>
+            </g>
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use stdweb::{js, Value};
+
+/// Reads text aloud through the Web Speech API. Does nothing where speech synthesis isn't
+/// available, including native builds.
+pub struct Speech {
+    available: bool,
+}
+
+impl Speech {
+    pub fn new() -> Self {
+        let available = cfg!(any(target_arch = "wasm32", target_arch = "asmjs")) && {
+            let supported = js! {
+                return "speechSynthesis" in window && "SpeechSynthesisUtterance" in window;
+            };
+            supported == Value::Bool(true)
+        };
+        Speech {
+            available: available,
+        }
+    }
+    /// Says `text` at `volume` from 0 to 1, cutting off whatever was being said.
+    pub fn speak(&mut self, text: &str, volume: f32) {
+        if !self.available {
+            return;
+        }
+        js! { @(no_return)
+            var utterance = new SpeechSynthesisUtterance(@{text});
+            utterance.rate = 0.85;
+            utterance.volume = @{volume};
+            window.speechSynthesis.cancel();
+            window.speechSynthesis.speak(utterance);
+        }
+    }
+    pub fn cancel(&mut self) {
+        if !self.available {
+            return;
+        }
+        js! { @(no_return)
+            window.speechSynthesis.cancel();
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use cgmath::{Vector2, InnerSpace};
+
+/// The walkable area of an overland map: a bounding box the player can't leave and a set of
+/// impassable polygons, all in land coordinates (y pointing up).
+pub struct Terrain {
+    pub min: Vector2<f32>,
+    pub max: Vector2<f32>,
+    pub walls: Vec<Vec<Vector2<f32>>>,
+}
+
+impl Terrain {
+    pub fn new(min: [f32; 2], max: [f32; 2]) -> Self {
+        Terrain {
+            min: min.into(),
+            max: max.into(),
+            walls: Vec::new(),
+        }
+    }
+    pub fn with_wall(mut self, points: &[[f32; 2]]) -> Self {
+        self.walls.push(points.iter().map(|&p| p.into()).collect());
+        self
+    }
+    pub fn blocked(&self, pos: Vector2<f32>, radius: f32) -> bool {
+        if pos.x - radius < self.min.x || pos.y - radius < self.min.y
+            || pos.x + radius > self.max.x || pos.y + radius > self.max.y {
+            return true;
+        }
+        self.walls.iter().any(|wall| circle_hits_polygon(wall, pos, radius))
+    }
+}
+
+/// Moves `pos` by `step` unless that lands somewhere `blocked`, in which case the move is tried
+/// along each axis on its own so the player slides along walls instead of sticking to them.
+pub fn slide<F: Fn(Vector2<f32>) -> bool>(pos: Vector2<f32>, step: Vector2<f32>, blocked: F) -> Vector2<f32> {
+    let candidates = [step, Vector2::new(step.x, 0.0), Vector2::new(0.0, step.y)];
+    for &candidate in candidates.iter() {
+        if candidate != Vector2::new(0.0, 0.0) && !blocked(pos + candidate) {
+            return pos + candidate;
+        }
+    }
+    pos
+}
+
+pub fn circle_hits_square(center: Vector2<f32>, half: f32, pos: Vector2<f32>, radius: f32) -> bool {
+    let dx = ((pos.x - center.x).abs() - half).max(0.0);
+    let dy = ((pos.y - center.y).abs() - half).max(0.0);
+    dx*dx + dy*dy < radius*radius
+}
+
+pub fn point_in_polygon(points: &[Vector2<f32>], pos: Vector2<f32>) -> bool {
+    let mut inside = false;
+    for i in 0..points.len() {
+        let a = points[i];
+        let b = points[(i + 1) % points.len()];
+        if (a.y > pos.y) != (b.y > pos.y) && pos.x < (b.x - a.x) * (pos.y - a.y) / (b.y - a.y) + a.x {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn circle_hits_polygon(points: &[Vector2<f32>], pos: Vector2<f32>, radius: f32) -> bool {
+    for i in 0..points.len() {
+        if segment_distance(points[i], points[(i + 1) % points.len()], pos) < radius {
+            return true;
+        }
+    }
+    point_in_polygon(points, pos)
+}
+
+fn segment_distance(a: Vector2<f32>, b: Vector2<f32>, p: Vector2<f32>) -> f32 {
+    let ab = b - a;
+    let len = ab.magnitude2();
+    let t = if len > 0.0 {
+        ((p - a).dot(ab) / len).max(0.0).min(1.0)
+    } else {
+        0.0
+    };
+    (a + ab * t - p).magnitude()
+}
@@ -1,10 +1,62 @@
-use yew::events::{KeyDownEvent, KeyUpEvent, IKeyboardEvent};
+use yew::{html, Html};
 
+use crate::context::*;
+use crate::Model;
 
+/// Width of one character of `.answertext`, which is set in a monospace font so the caret can be
+/// placed without measuring the text.
+const CHAR_WIDTH: f32 = 18.0;
+
+/// Which characters a problem's answer box accepts.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InputFilter {
+    Any,
+    /// Whole or decimal numbers, possibly negative.
+    Number,
+    /// Numbers and fractions like `3/4`.
+    Fraction,
+    /// Times like `3:05`.
+    Time,
+    Letters,
+}
+
+impl InputFilter {
+    /// Guesses the filter from the expected answer.
+    pub fn for_answer(answer: &str) -> Self {
+        if answer.chars().all(|c| c.is_ascii_digit() || c == '-' || c == '.') {
+            InputFilter::Number
+        } else if answer.chars().all(|c| c.is_ascii_digit() || c == '/' || c == '-') {
+            InputFilter::Fraction
+        } else if answer.chars().all(|c| c.is_ascii_digit() || c == ':') {
+            InputFilter::Time
+        } else if answer.chars().all(|c| c.is_alphabetic() || c == ' ' || c == '\'') {
+            InputFilter::Letters
+        } else {
+            InputFilter::Any
+        }
+    }
+    pub fn accepts(&self, c: char) -> bool {
+        match self {
+            InputFilter::Any => !c.is_control(),
+            InputFilter::Number => c.is_ascii_digit() || c == '-' || c == '.',
+            InputFilter::Fraction => c.is_ascii_digit() || c == '-' || c == '/',
+            InputFilter::Time => c.is_ascii_digit() || c == ':',
+            InputFilter::Letters => c.is_alphabetic() || c == ' ' || c == '\'',
+        }
+    }
+}
+
+/// The answer being typed. `cursor` and `anchor` count characters, not bytes; while shift is held
+/// the caret moves away from `anchor` and everything between them is selected.
 pub struct TextBox {
     pub text: String,
     pub cursor: usize,
+    pub anchor: Option<usize>,
+    /// Whether the arrow keys move the caret. They walk the player on the overland instead.
     pub movement: bool,
+    pub filter: InputFilter,
+    shift: bool,
+    control: bool,
 }
 
 impl TextBox {
@@ -12,44 +64,143 @@ impl TextBox {
         TextBox {
             text: "".into(),
             cursor: 0,
-            movement: true
+            anchor: None,
+            movement: true,
+            filter: InputFilter::Any,
+            shift: false,
+            control: false,
+        }
+    }
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+    /// The byte offset of the character at `index`.
+    fn byte(&self, index: usize) -> usize {
+        self.text.char_indices().nth(index).map(|(i, _)| i).unwrap_or_else(|| self.text.len())
+    }
+    /// The selected range of characters, if any.
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        match self.anchor {
+            Some(anchor) if anchor != self.cursor => Some((anchor.min(self.cursor), anchor.max(self.cursor))),
+            _ => None,
+        }
+    }
+    fn remove(&mut self, start: usize, end: usize) {
+        let (a, b) = (self.byte(start), self.byte(end));
+        self.text.replace_range(a..b, "");
+        self.cursor = start;
+        self.anchor = None;
+    }
+    /// Deletes the selection, returning whether there was one.
+    fn remove_selection(&mut self) -> bool {
+        if let Some((start, end)) = self.selection() {
+            self.remove(start, end);
+            true
+        } else {
+            self.anchor = None;
+            false
         }
     }
+    fn insert(&mut self, c: char) {
+        if !self.filter.accepts(c) {
+            return;
+        }
+        self.remove_selection();
+        let at = self.byte(self.cursor);
+        self.text.insert(at, c);
+        self.cursor += 1;
+    }
     fn backspace(&mut self) {
-        if self.cursor != 0 {
-            self.text = String::from(&self.text[0..(self.cursor-1)]) + &self.text[self.cursor..self.text.len()];
-            self.cursor -= 1;
+        if !self.remove_selection() && self.cursor != 0 {
+            self.remove(self.cursor - 1, self.cursor);
         }
     }
-    fn left(&mut self) {
-        if self.movement {
-            if self.cursor > 0 {
-                self.cursor -= 1;
+    fn delete(&mut self) {
+        if !self.remove_selection() && self.cursor < self.len() {
+            self.remove(self.cursor, self.cursor + 1);
+        }
+    }
+    /// Moves the caret to `to`, extending the selection while shift is held.
+    fn move_to(&mut self, to: usize) {
+        if !self.movement {
+            return;
+        }
+        if self.shift {
+            if self.anchor.is_none() {
+                self.anchor = Some(self.cursor);
             }
+        } else {
+            self.anchor = None;
+        }
+        self.cursor = to.min(self.len());
+    }
+    fn left(&mut self) {
+        match self.selection() {
+            Some((start, _)) if !self.shift => self.move_to(start),
+            _ => self.move_to(self.cursor.saturating_sub(1)),
         }
     }
     fn right(&mut self) {
-        if self.movement {
-            if self.text.chars().count() <= self.cursor {
-                self.cursor = self.text.chars().count();
-            } else {
-                self.cursor += 1;
-            }
+        match self.selection() {
+            Some((_, end)) if !self.shift => self.move_to(end),
+            _ => self.move_to(self.cursor + 1),
         }
     }
-    pub fn down(&mut self, e: &KeyDownEvent) {
-        match e.key().as_str() {
+    fn select_all(&mut self) {
+        self.anchor = Some(0);
+        self.cursor = self.len();
+    }
+    pub fn down(&mut self, key: &str) {
+        match key {
+            "Shift" => self.shift = true,
+            "Control" | "Meta" => self.control = true,
             "ArrowRight" => self.right(),
             "ArrowLeft" => self.left(),
+            "Home" => self.move_to(0),
+            "End" => { let end = self.len(); self.move_to(end) },
             "Backspace" => self.backspace(),
-            x if x.len() == 1 => {
-                self.text = String::from(&self.text[0..self.cursor]) + &x + &self.text[self.cursor..self.text.len()];
-                self.cursor += 1;
+            "Delete" => self.delete(),
+            "a" | "A" if self.control => self.select_all(),
+            _ if self.control => (),
+            x => {
+                let mut chars = x.chars();
+                if let (Some(c), None) = (chars.next(), chars.next()) {
+                    self.insert(c);
+                }
             },
-            _ => ()
-
         }
     }
-    pub fn up(&mut self, _e: &KeyUpEvent) {
+    pub fn up(&mut self, key: &str) {
+        match key {
+            "Shift" => self.shift = false,
+            "Control" | "Meta" => self.control = false,
+            _ => (),
+        }
+    }
+    /// Draws the box with the answer, selection and a blinking caret, centred on the origin.
+    pub fn render(&self, ctx: &Context) -> Html<Model> {
+        let x = |index: usize| -240.0 + index as f32 * CHAR_WIDTH;
+        let caret = !ctx.animations || ctx.anim_t % 1.0 < 0.6;
+        html! {
+            <g class="text_box_group", role="textbox", aria-label={format!("Answer: {}", self.text)},>
+                <rect class="text_box", x=-250, y=-25, width=500, height=50, rx=10, ry=10,></rect>
+                {
+                    match self.selection() {
+                        Some((start, end)) => html! {
+                            <rect class="selection", x={x(start)}, y=-18, width={(end - start) as f32 * CHAR_WIDTH}, height=36,></rect>
+                        },
+                        None => html! { <g></g> },
+                    }
+                }
+                <text x=-240, y=12, class="answertext",>{&self.text}</text>
+                {
+                    if caret {
+                        html! { <line class="caret", x1={x(self.cursor)}, y1=-16, x2={x(self.cursor)}, y2=16,></line> }
+                    } else {
+                        html! { <g></g> }
+                    }
+                }
+            </g>
+        }
     }
 }
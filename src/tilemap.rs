@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+
+use serde_derive::Deserialize;
+use serde_json::Value;
+use yew::{html, Html};
+use cgmath::Vector2;
+
+use crate::Model;
+use crate::terrain::Terrain;
+
+/// Gids in Tiled layers carry flip flags in their top three bits.
+const GID_MASK: u32 = 0x1fff_ffff;
+
+#[derive(Deserialize)]
+struct TiledMap {
+    #[serde(default)]
+    infinite: bool,
+    width: usize,
+    height: usize,
+    tilewidth: f32,
+    tileheight: f32,
+    layers: Vec<TiledLayer>,
+    tilesets: Vec<TiledTileset>,
+}
+
+#[derive(Deserialize)]
+struct TiledLayer {
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    data: Vec<u32>,
+    #[serde(default)]
+    chunks: Vec<Value>,
+    #[serde(default)]
+    objects: Vec<TiledObject>,
+}
+
+#[derive(Deserialize)]
+struct TiledObject {
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type", default)]
+    kind: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    #[serde(default)]
+    polygon: Option<Vec<TiledPoint>>,
+    #[serde(default)]
+    properties: Vec<TiledProperty>,
+}
+
+#[derive(Deserialize)]
+struct TiledPoint {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Deserialize)]
+struct TiledProperty {
+    name: String,
+    value: Value,
+}
+
+#[derive(Deserialize)]
+struct TiledTileset {
+    firstgid: u32,
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(default)]
+    image: String,
+    #[serde(default)]
+    imagewidth: f32,
+    #[serde(default)]
+    imageheight: f32,
+    #[serde(default)]
+    columns: u32,
+    #[serde(default)]
+    tiles: Vec<TiledTile>,
+}
+
+#[derive(Deserialize)]
+struct TiledTile {
+    id: u32,
+    #[serde(default)]
+    properties: Vec<TiledProperty>,
+}
+
+fn properties(props: Vec<TiledProperty>) -> HashMap<String, Value> {
+    props.into_iter().map(|p| (p.name, p.value)).collect()
+}
+
+pub struct Tileset {
+    pub first_gid: u32,
+    pub image: String,
+    pub image_size: [f32; 2],
+    pub columns: u32,
+}
+
+/// Per-tile data read from the tileset's custom properties.
+#[derive(Debug, Clone)]
+pub struct TileInfo {
+    pub terrain: String,
+    pub walkable: bool,
+}
+
+/// A shape from one of the map's object layers, converted to land coordinates.
+pub struct MapObject {
+    pub layer: String,
+    pub name: String,
+    pub kind: String,
+    pub points: Vec<Vector2<f32>>,
+    pub properties: HashMap<String, Value>,
+}
+
+impl MapObject {
+    pub fn property_f32(&self, name: &str) -> Option<f32> {
+        self.properties.get(name).and_then(|v| v.as_f64()).map(|v| v as f32)
+    }
+    pub fn property_str(&self, name: &str) -> Option<&str> {
+        self.properties.get(name).and_then(|v| v.as_str())
+    }
+    pub fn center(&self) -> Vector2<f32> {
+        let sum = self.points.iter().fold(Vector2::new(0.0, 0.0), |acc, &p| acc + p);
+        sum / (self.points.len().max(1) as f32)
+    }
+}
+
+/// An overland map in the layout Tiled exports as JSON: tile layers drawn bottom to top and
+/// object layers for anything that isn't a tile. The map is centered on the land origin.
+pub struct TileMap {
+    pub columns: usize,
+    pub rows: usize,
+    pub tile_size: [f32; 2],
+    pub layers: Vec<Vec<u32>>,
+    pub tilesets: Vec<Tileset>,
+    pub tiles: HashMap<u32, TileInfo>,
+    pub objects: Vec<MapObject>,
+}
+
+impl TileMap {
+    pub fn from_tiled(json: &str) -> Result<Self, String> {
+        let map: TiledMap = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        if map.infinite {
+            return Err("infinite maps aren't supported".into());
+        }
+        if map.tilewidth <= 0.0 || map.tileheight <= 0.0 {
+            return Err("map tiles have no size".into());
+        }
+        let mut tiles = HashMap::new();
+        let mut tilesets = Vec::new();
+        for set in map.tilesets {
+            if let Some(source) = set.source {
+                return Err(format!("external tileset {} has to be embedded in the map", source));
+            }
+            if set.columns == 0 {
+                return Err(format!("tileset {} has no columns", set.image));
+            }
+            for tile in set.tiles {
+                let props = properties(tile.properties);
+                tiles.insert(set.firstgid + tile.id, TileInfo {
+                    terrain: props.get("terrain").and_then(|v| v.as_str()).unwrap_or("").into(),
+                    walkable: props.get("walkable").and_then(|v| v.as_bool()).unwrap_or(true),
+                });
+            }
+            tilesets.push(Tileset {
+                first_gid: set.firstgid,
+                image: set.image,
+                image_size: [set.imagewidth, set.imageheight],
+                columns: set.columns,
+            });
+        }
+        tilesets.sort_by_key(|set| set.first_gid);
+        let origin = [
+            -(map.width as f32) * map.tilewidth / 2.0,
+            -(map.height as f32) * map.tileheight / 2.0,
+        ];
+        let mut layers = Vec::new();
+        let mut objects = Vec::new();
+        for layer in map.layers {
+            match layer.kind.as_str() {
+                "tilelayer" => {
+                    if !layer.chunks.is_empty() {
+                        return Err(format!("layer {} is split into chunks", layer.name));
+                    }
+                    if layer.data.len() != map.width * map.height {
+                        return Err(format!("layer {} has the wrong number of tiles", layer.name));
+                    }
+                    layers.push(layer.data.iter().map(|gid| gid & GID_MASK).collect());
+                },
+                "objectgroup" => {
+                    for object in layer.objects {
+                        let corner = [origin[0] + object.x, origin[1] + object.y];
+                        let screen: Vec<[f32; 2]> = match object.polygon {
+                            Some(polygon) => polygon.iter().map(|p| [corner[0] + p.x, corner[1] + p.y]).collect(),
+                            None => vec![
+                                corner,
+                                [corner[0] + object.width, corner[1]],
+                                [corner[0] + object.width, corner[1] + object.height],
+                                [corner[0], corner[1] + object.height],
+                            ],
+                        };
+                        objects.push(MapObject {
+                            layer: layer.name.clone(),
+                            name: object.name,
+                            kind: object.kind,
+                            points: screen.iter().map(|p| Vector2::new(p[0], -p[1])).collect(),
+                            properties: properties(object.properties),
+                        });
+                    }
+                },
+                _ => (),
+            }
+        }
+        Ok(TileMap {
+            columns: map.width,
+            rows: map.height,
+            tile_size: [map.tilewidth, map.tileheight],
+            layers: layers,
+            tilesets: tilesets,
+            tiles: tiles,
+            objects: objects,
+        })
+    }
+    /// Top left corner of the map in screen coordinates (y pointing down).
+    fn origin(&self) -> [f32; 2] {
+        [
+            -(self.columns as f32) * self.tile_size[0] / 2.0,
+            -(self.rows as f32) * self.tile_size[1] / 2.0,
+        ]
+    }
+    fn cell(&self, pos: Vector2<f32>) -> Option<(usize, usize)> {
+        let origin = self.origin();
+        let col = ((pos.x - origin[0]) / self.tile_size[0]).floor();
+        let row = ((-pos.y - origin[1]) / self.tile_size[1]).floor();
+        if col < 0.0 || row < 0.0 || col >= self.columns as f32 || row >= self.rows as f32 {
+            None
+        } else {
+            Some((col as usize, row as usize))
+        }
+    }
+    /// Terrain type of the topmost tile at `pos` that has one.
+    pub fn terrain_at(&self, pos: Vector2<f32>) -> Option<&str> {
+        let (col, row) = self.cell(pos)?;
+        self.layers.iter().rev()
+            .filter_map(|layer| self.tiles.get(&layer[row*self.columns + col]))
+            .map(|info| info.terrain.as_str())
+            .find(|terrain| !terrain.is_empty())
+    }
+    /// Collision for the map: its edges, every tile marked unwalkable, and the shapes in the
+    /// `collision` object layer.
+    pub fn terrain(&self) -> Terrain {
+        let origin = self.origin();
+        let [w, h] = self.tile_size;
+        let mut terrain = Terrain::new([origin[0], origin[1]], [-origin[0], -origin[1]]);
+        for row in 0..self.rows {
+            for col in 0..self.columns {
+                let solid = self.layers.iter()
+                    .filter_map(|layer| self.tiles.get(&layer[row*self.columns + col]))
+                    .any(|info| !info.walkable);
+                if solid {
+                    let (x, y) = (origin[0] + col as f32 * w, -(origin[1] + row as f32 * h));
+                    terrain = terrain.with_wall(&[[x, y], [x + w, y], [x + w, y - h], [x, y - h]]);
+                }
+            }
+        }
+        for object in self.objects.iter().filter(|o| o.layer == "collision") {
+            terrain.walls.push(object.points.clone());
+        }
+        terrain
+    }
+    fn tileset(&self, gid: u32) -> Option<&Tileset> {
+        self.tilesets.iter().rev().find(|set| set.first_gid <= gid)
+    }
+    /// Draws the tiles within `radius` of `center` (both in land coordinates).
+    pub fn render(&self, center: Vector2<f32>, radius: [f32; 2]) -> Html<Model> {
+        let origin = self.origin();
+        let [w, h] = self.tile_size;
+        let span = |c: f32, r: f32, o: f32, size: f32, count: usize| {
+            let lo = ((c - r - o) / size).floor().max(0.0) as usize;
+            let hi = (((c + r - o) / size).ceil().max(0.0) as usize).min(count);
+            lo..hi
+        };
+        let cols = span(center.x, radius[0], origin[0], w, self.columns);
+        let rows = span(-center.y, radius[1], origin[1], h, self.rows);
+        html! {
+            <g class="tiles",>
+            {for self.layers.iter().map(|layer| {
+                html! {
+                    <g>
+                    {for rows.clone().flat_map(|row| cols.clone().map(move |col| (col, row))).map(|(col, row)| {
+                        let gid = layer[row*self.columns + col];
+                        match self.tileset(gid) {
+                            Some(set) if gid != 0 => {
+                                let index = gid - set.first_gid;
+                                let (sx, sy) = ((index % set.columns) as f32 * w, (index / set.columns) as f32 * h);
+                                html! {
+                                    <svg x={origin[0] + col as f32 * w}, y={origin[1] + row as f32 * h},
+                                        width=w, height=h, viewBox={format!("{} {} {} {}", sx, sy, w, h)},>
+                                        <image href={&set.image}, width={set.image_size[0]}, height={set.image_size[1]},></image>
+                                    </svg>
+                                }
+                            },
+                            _ => html! { <g></g> },
+                        }
+                    })}
+                    </g>
+                }
+            })}
+            </g>
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(tile: &str, layer: &str, tileset: &str) -> String {
+        format!(r#"{{"width": 2, "height": 1, {}, "layers": [{}], "tilesets": [{}]}}"#, tile, layer, tileset)
+    }
+
+    const TILE: &str = r#""tilewidth": 32, "tileheight": 32"#;
+    const LAYER: &str = r#"{"name": "ground", "type": "tilelayer", "data": [1, 2]}"#;
+    const TILESET: &str = r#"{"firstgid": 1, "image": "tiles.png", "imagewidth": 64, "imageheight": 32, "columns": 2}"#;
+
+    #[test]
+    fn loads() {
+        let map = TileMap::from_tiled(&map(TILE, LAYER, TILESET)).unwrap();
+        assert_eq!((map.columns, map.rows), (2, 1));
+        assert_eq!(map.layers, vec![vec![1, 2]]);
+    }
+
+    #[test]
+    fn tileset_needs_columns() {
+        let tileset = TILESET.replace(r#""columns": 2"#, r#""columns": 0"#);
+        assert!(TileMap::from_tiled(&map(TILE, LAYER, &tileset)).is_err());
+    }
+
+    #[test]
+    fn tiles_need_a_size() {
+        assert!(TileMap::from_tiled(&map(r#""tilewidth": 0, "tileheight": 32"#, LAYER, TILESET)).is_err());
+    }
+
+    #[test]
+    fn infinite_maps_are_rejected() {
+        let chunked = r#"{"name": "ground", "type": "tilelayer", "chunks": [{"x": 0, "y": 0, "width": 2, "height": 1, "data": [1, 2]}]}"#;
+        let json = map(TILE, chunked, TILESET).replacen('{', r#"{"infinite": true, "#, 1);
+        assert!(TileMap::from_tiled(&json).is_err());
+        assert!(TileMap::from_tiled(&map(TILE, chunked, TILESET)).is_err());
+    }
+
+    #[test]
+    fn external_tilesets_are_rejected() {
+        assert!(TileMap::from_tiled(&map(TILE, LAYER, r#"{"firstgid": 1, "source": "tiles.tsx"}"#)).is_err());
+    }
+}
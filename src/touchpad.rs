@@ -0,0 +1,74 @@
+use yew::{html, Html};
+use yew::events::IEvent;
+
+use crate::{Model, Msg};
+
+/// The number pad, row by row, as (label, key) pairs. Each button presses the same key a keyboard
+/// would, so answers typed by touch go through `TextBox::down` like any other.
+const NUMPAD: &[&[(&str, &str)]] = &[
+    &[("7", "7"), ("8", "8"), ("9", "9")],
+    &[("4", "4"), ("5", "5"), ("6", "6")],
+    &[("1", "1"), ("2", "2"), ("3", "3")],
+    &[("−", "-"), ("0", "0"), (".", ".")],
+    &[("/", "/"), ("⌫", "Backspace"), ("⏎", "Enter")],
+];
+
+const DPAD: &[(&str, &str, f32, f32)] = &[
+    ("▲", "ArrowUp", 0.0, -1.0),
+    ("▼", "ArrowDown", 0.0, 1.0),
+    ("◀", "ArrowLeft", -1.0, 0.0),
+    ("▶", "ArrowRight", 1.0, 0.0),
+];
+
+const KEY_SIZE: f32 = 56.0;
+
+/// A button that holds `key` down for as long as it is pressed by mouse or touch. Touches cancel
+/// the mouse events the browser would otherwise fire after them so keys are not pressed twice.
+pub fn button(label: &str, key: &'static str, x: f32, y: f32, width: f32, height: f32) -> Html<Model> {
+    html! {
+        <g class="touch_key",
+            onmousedown=move |_| Msg::Press(key.into()),
+            onmouseup=move |_| Msg::Release(key.into()),
+            onmouseleave=move |_| Msg::Release(key.into()),
+            ontouchstart=move |e| { e.prevent_default(); Msg::Press(key.into()) },
+            ontouchend=move |e| { e.prevent_default(); Msg::Release(key.into()) },
+            ontouchcancel=move |_| Msg::Release(key.into()),>
+            <rect x=x, y=y, width=width, height=height, rx=8, ry=8,></rect>
+            <text x={x + width / 2.0}, y={y + height / 2.0 + 9.0},>{ label }</text>
+        </g>
+    }
+}
+
+/// Digits, minus, slash, decimal point, backspace and enter with its top left corner on the origin.
+pub fn numpad() -> Html<Model> {
+    html! {
+        <g class="numpad",>
+        {for NUMPAD.iter().enumerate().flat_map(|(row, keys)| {
+            keys.iter().enumerate().map(move |(col, &(label, key))| {
+                button(label, key, col as f32 * (KEY_SIZE + 4.0), row as f32 * (KEY_SIZE + 4.0), KEY_SIZE, KEY_SIZE)
+            })
+        })}
+        </g>
+    }
+}
+
+/// Arrow keys around the origin for walking and menus.
+pub fn dpad() -> Html<Model> {
+    html! {
+        <g class="dpad",>
+        {for DPAD.iter().map(|&(label, key, dx, dy)| {
+            button(label, key, dx * KEY_SIZE - KEY_SIZE / 2.0, dy * KEY_SIZE - KEY_SIZE / 2.0, KEY_SIZE, KEY_SIZE)
+        })}
+        </g>
+    }
+}
+
+/// Confirm and back buttons for screens driven by Enter and Escape.
+pub fn menu_buttons() -> Html<Model> {
+    html! {
+        <g class="menu_buttons",>
+            { button("OK", "Enter", 0.0, 0.0, 100.0, KEY_SIZE) }
+            { button("Back", "Escape", 0.0, KEY_SIZE + 4.0, 100.0, KEY_SIZE) }
+        </g>
+    }
+}
@@ -0,0 +1,107 @@
+use yew::{html, Html};
+
+use crate::anim::*;
+use crate::Model;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TransitionKind {
+    Fade,
+    Swirl,
+    Wipe,
+    Zoom,
+}
+
+/// Which transition plays when the game moves from one state to another, by `State::name`.
+/// The first matching row wins and `"*"` matches any state.
+pub const TRANSITIONS: &[(&str, &str, TransitionKind)] = &[
+    ("overland", "battle", TransitionKind::Zoom),
+    ("battle", "overland", TransitionKind::Swirl),
+    ("title", "*", TransitionKind::Wipe),
+    ("*", "*", TransitionKind::Fade),
+];
+
+pub fn transition_between(from: &str, to: &str) -> TransitionKind {
+    TRANSITIONS.iter()
+        .find(|&&(f, t, _)| (f == from || f == "*") && (t == to || t == "*"))
+        .map(|&(_, _, kind)| kind)
+        .unwrap_or(TransitionKind::Fade)
+}
+
+/// Reveals a newly entered state. The game ignores key presses until it has finished.
+pub struct Transition {
+    pub kind: TransitionKind,
+    pub time: f32,
+    /// The point on screen a zoom pulls out from.
+    pub focus: [f32; 2],
+}
+
+impl Transition {
+    pub fn new(kind: TransitionKind, focus: [f32; 2]) -> Self {
+        Transition {
+            kind: kind,
+            time: 0.0,
+            focus: focus,
+        }
+    }
+    fn tween(&self) -> Tween {
+        let (duration, ease) = match self.kind {
+            TransitionKind::Fade => (0.5, Ease::Linear),
+            TransitionKind::Swirl => (1.0, Ease::OutQuad),
+            TransitionKind::Wipe => (0.8, Ease::InQuad),
+            TransitionKind::Zoom => (0.8, Ease::OutQuad),
+        };
+        Tween {
+            from: 0.0,
+            to: 1.0,
+            duration: duration,
+            ease: ease,
+        }
+    }
+    pub fn tick(&mut self, delta: f32) {
+        self.time += delta;
+    }
+    pub fn finished(&self) -> bool {
+        self.time >= self.tween().duration
+    }
+    /// Draws `scene`, which fills a viewport of `size`, part way through being revealed.
+    pub fn render(&self, scene: Html<Model>, size: [f32; 2]) -> Html<Model> {
+        let p = self.tween().at(self.time);
+        let [width, height] = size;
+        let (cx, cy) = (width / 2.0, height / 2.0);
+        match self.kind {
+            TransitionKind::Fade => html! {
+                <g>
+                    { scene }
+                    <rect class="transition", x=0, y=0, width=width, height=height, style={format!("opacity: {}", 1.0 - p)},></rect>
+                </g>
+            },
+            TransitionKind::Wipe => html! {
+                <g>
+                    { scene }
+                    <rect class="transition", x={p * width}, y=0, width=width, height=height,></rect>
+                </g>
+            },
+            TransitionKind::Swirl => {
+                let r = p * width.max(height) * 0.6;
+                let iris = format!("M 0 0 H {w} V {h} H 0 Z M {} {cy} A {r} {r} 0 1 0 {} {cy} A {r} {r} 0 1 0 {} {cy} Z",
+                    cx - r, cx + r, cx - r, w = width, h = height, cy = cy, r = r);
+                html! {
+                    <g>
+                        <g transform={format!("rotate({} {} {})", (1.0 - p) * -270.0, cx, cy)},>{ scene }</g>
+                        <path class="transition iris", d=iris,></path>
+                    </g>
+                }
+            },
+            TransitionKind::Zoom => {
+                let (x, y) = (self.focus[0], self.focus[1]);
+                let scale = 1.0 + (1.0 - p) * 3.0;
+                html! {
+                    <g>
+                        <g transform={format!("translate({}, {}) scale({}) translate({}, {})", x, y, scale, -x, -y)},>{ scene }</g>
+                        <rect class="transition", x=0, y=0, width=width, height=height, style={format!("opacity: {}", 1.0 - p)},></rect>
+                    </g>
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+const ONES: [&str; 20] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen",
+];
+const TENS: [&str; 10] = ["", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety"];
+const SCALES: [(u64, &str); 6] = [
+    (1_000_000_000_000_000_000, "quintillion"),
+    (1_000_000_000_000_000, "quadrillion"),
+    (1_000_000_000_000, "trillion"),
+    (1_000_000_000, "billion"),
+    (1_000_000, "million"),
+    (1_000, "thousand"),
+];
+
+/// Words for 1 to 999.
+fn below_thousand(n: u64) -> String {
+    let mut words = Vec::new();
+    if n >= 100 {
+        words.push(format!("{} hundred", ONES[(n / 100) as usize]));
+    }
+    let rest = (n % 100) as usize;
+    if rest >= 20 && rest % 10 == 0 {
+        words.push(TENS[rest / 10].to_string());
+    } else if rest >= 20 {
+        words.push(format!("{}-{}", TENS[rest / 10], ONES[rest % 10]));
+    } else if rest > 0 {
+        words.push(ONES[rest].to_string());
+    }
+    words.join(" ")
+}
+
+fn whole_words(n: u64) -> String {
+    if n == 0 {
+        return ONES[0].into();
+    }
+    let mut words = Vec::new();
+    let mut rest = n;
+    for &(scale, name) in SCALES.iter() {
+        if rest >= scale {
+            words.push(format!("{} {}", below_thousand(rest / scale), name));
+            rest %= scale;
+        }
+    }
+    if rest > 0 {
+        words.push(below_thousand(rest));
+    }
+    words.join(" ")
+}
+
+/// How `n` is said aloud, e.g. 536 is "five hundred thirty-six".
+pub fn number_words(n: i64) -> String {
+    let words = whole_words((n as i128).abs() as u64);
+    if n < 0 { format!("minus {}", words) } else { words }
+}
+
+/// The ordinal of `n`, e.g. 3 is "third" and 21 is "twenty-first".
+pub fn ordinal_words(n: i64) -> String {
+    let words = number_words(n);
+    let split = words.rfind(|c| c == ' ' || c == '-').map_or(0, |i| i + 1);
+    let (head, last) = words.split_at(split);
+    let last = match last {
+        "one" => "first".to_string(),
+        "two" => "second".to_string(),
+        "three" => "third".to_string(),
+        "five" => "fifth".to_string(),
+        "eight" => "eighth".to_string(),
+        "nine" => "ninth".to_string(),
+        "twelve" => "twelfth".to_string(),
+        l if l.ends_with('y') => format!("{}ieth", &l[..l.len() - 1]),
+        l => format!("{}th", l),
+    };
+    format!("{}{}", head, last)
+}
+
+/// How the fraction `n/d` is said aloud, e.g. 3/4 is "three fourths".
+pub fn fraction_words(n: i64, d: i64) -> String {
+    // The sign is read on the numerator, so -2/3 and 2/-3 are both "minus two thirds".
+    let (n, d) = if d < 0 { (n.saturating_neg(), d.saturating_neg()) } else { (n, d) };
+    let denominator = match d {
+        1 => return number_words(n),
+        2 => "half".to_string(),
+        d => ordinal_words(d),
+    };
+    if n.abs() == 1 {
+        format!("{} {}", number_words(n), denominator)
+    } else if d == 2 {
+        format!("{} halves", number_words(n))
+    } else {
+        format!("{} {}s", number_words(n), denominator)
+    }
+}
+
+/// Reads the digits at the front of `chars` as a number.
+fn take_number(chars: &mut Peekable<Chars>) -> Option<i64> {
+    let mut n: Option<i64> = None;
+    while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+        chars.next();
+        n = Some(n.unwrap_or(0).saturating_mul(10).saturating_add(digit as i64));
+    }
+    n
+}
+
+/// How a written problem such as "536+329=?" is said aloud. Numbers written `n/d` are read as
+/// fractions and words are kept as they are.
+pub fn expression_words(text: &str) -> String {
+    let mut words = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if let Some(n) = take_number(&mut chars) {
+            if chars.peek() == Some(&'/') {
+                chars.next();
+                match take_number(&mut chars) {
+                    Some(d) if d != 0 => words.push(fraction_words(n, d)),
+                    Some(d) => words.push(format!("{} divided by {}", number_words(n), number_words(d))),
+                    None => words.push(format!("{} divided by", number_words(n))),
+                }
+            } else {
+                words.push(number_words(n));
+            }
+            continue;
+        }
+        chars.next();
+        if c.is_alphabetic() {
+            let mut word = c.to_string();
+            while let Some(&c) = chars.peek().filter(|c| c.is_alphabetic() || **c == '\'') {
+                word.push(c);
+                chars.next();
+            }
+            words.push(word);
+            continue;
+        }
+        let word = match c {
+            '+' => "plus",
+            '-' | '−' => "minus",
+            '×' | '*' => "times",
+            '÷' | '/' => "divided by",
+            '=' => "equals",
+            '?' => "what",
+            _ => continue,
+        };
+        words.push(word.to_string());
+    }
+    words.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        assert_eq!(number_words(0), "zero");
+        assert_eq!(number_words(7), "seven");
+        assert_eq!(number_words(13), "thirteen");
+        assert_eq!(number_words(19), "nineteen");
+        assert_eq!(number_words(40), "forty");
+        assert_eq!(number_words(90), "ninety");
+        assert_eq!(number_words(536), "five hundred thirty-six");
+        assert_eq!(number_words(1000), "one thousand");
+        assert_eq!(number_words(1_000_001), "one million one");
+        assert_eq!(number_words(-45), "minus forty-five");
+    }
+
+    #[test]
+    fn ordinals() {
+        assert_eq!(ordinal_words(1), "first");
+        assert_eq!(ordinal_words(3), "third");
+        assert_eq!(ordinal_words(12), "twelfth");
+        assert_eq!(ordinal_words(20), "twentieth");
+        assert_eq!(ordinal_words(21), "twenty-first");
+        assert_eq!(ordinal_words(100), "one hundredth");
+    }
+
+    #[test]
+    fn fractions() {
+        assert_eq!(fraction_words(1, 2), "one half");
+        assert_eq!(fraction_words(3, 2), "three halves");
+        assert_eq!(fraction_words(3, 4), "three fourths");
+        assert_eq!(fraction_words(1, 3), "one third");
+        assert_eq!(fraction_words(5, 1), "five");
+        assert_eq!(fraction_words(-2, 3), "minus two thirds");
+        assert_eq!(fraction_words(2, -3), "minus two thirds");
+        assert_eq!(fraction_words(-2, -3), "two thirds");
+        assert_eq!(fraction_words(1, -2), "minus one half");
+    }
+
+    #[test]
+    fn expressions() {
+        assert_eq!(expression_words("536+329=?"), "five hundred thirty-six plus three hundred twenty-nine equals what");
+        assert_eq!(expression_words("1/2+3/4=?"), "one half plus three fourths equals what");
+        assert_eq!(expression_words("12×11=?"), "twelve times eleven equals what");
+        assert_eq!(expression_words("100-7=?"), "one hundred minus seven equals what");
+    }
+}